@@ -0,0 +1,45 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+/// @title Registry of the Merkle roots of signed contracts anchored by the backend canister
+/// @notice The leaf of a contract is keccak256(contract id ++ content hash), the 16 uuid bytes followed by
+/// the 32 bytes of the content hash. Pairs are hashed in sorted order, so proofs can also be checked
+/// with OpenZeppelin's MerkleProof.verify against a registered root.
+contract ContractRegistry {
+    /// @notice Ethereum address of the canister's threshold ECDSA key, the only one allowed to anchor roots
+    address public immutable canisterAddress;
+
+    /// @notice Timestamp of the block a root was first anchored in, zero if it never was
+    mapping(bytes32 => uint256) public anchoredAt;
+
+    event RootAnchored(bytes32 indexed root, uint256 count, address indexed submitter);
+
+    error NotCanister(address sender);
+
+    constructor(address canisterAddress_) {
+        canisterAddress = canisterAddress_;
+    }
+
+    /// @notice Register the Merkle root of count contracts.
+    /// Anchoring a root again does not revert, the canister resubmits roots it could not confirm.
+    function anchor(bytes32 root, uint256 count) external {
+        if (msg.sender != canisterAddress) revert NotCanister(msg.sender);
+
+        if (anchoredAt[root] == 0) {
+            anchoredAt[root] = block.timestamp;
+        }
+        emit RootAnchored(root, count, msg.sender);
+    }
+
+    /// @notice Check that a contract leaf belongs to an anchored root, given the proof stored in the contract's anchor
+    function isAnchored(bytes32 leaf, bytes32[] calldata proof, bytes32 root) external view returns (bool) {
+        bytes32 hash = leaf;
+        for (uint256 i = 0; i < proof.length; i++) {
+            bytes32 sibling = proof[i];
+            hash = hash < sibling
+                ? keccak256(abi.encodePacked(hash, sibling))
+                : keccak256(abi.encodePacked(sibling, hash));
+        }
+        return hash == root && anchoredAt[root] != 0;
+    }
+}
\ No newline at end of file
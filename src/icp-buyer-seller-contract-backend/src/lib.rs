@@ -1,9 +1,11 @@
 use alloy::primitives::Address;
-use ic_cdk::init;
-use repositories::{ApiError, Contract, Role, User, UserRepositoryImpl};
+use std::time::Duration;
+use ic_cdk::{init, post_upgrade};
+use repositories::{ApiError, Attachment, AttestationPublicKey, ContractAttestation, AuditPage, CertifiedContract, CertifiedSignatureStatus, Config, GetArchivesArgs, GetBlocksArgs, GetBlocksResult, ICRC3ArchiveInfo, ICRC3DataCertificate, SupportedBlockType, Contract, ContractDetails, ContractPage, ContractSchema, ContractSummary, ContractTemplate, ContractVersion, DenylistEntry, DenylistSubject, EvmSignature, ListContractsRequest, MessagePage, Profile, Permission, ProfileUpdate, Role, SignatureQuorum, SignatoryRole, SignedDelegation, SiweLoginDetails, SiwePreparedLogin, User, UserRepositoryImpl, Verification, VerificationLevel};
 use candid::{Principal, CandidType, Deserialize};
-use services::{AccessControlServiceImpl, AccessControlService, ContractService, ContractServiceImpl, UserService, UserServiceImpl, WalletService, WalletServiceImpl};
-use repositories::{Uuid};
+use serde_bytes::ByteBuf;
+use services::{AccessControlServiceImpl, AccessControlService, AnchorService, AnchorServiceImpl, AttestationService, AttestationServiceImpl, AttachmentService, AttachmentServiceImpl, AuditService, AuditServiceImpl, ConfigService, ConfigServiceImpl, ContractService, ContractServiceImpl, DenylistService, DenylistServiceImpl, ICRC3Service, ICRC3ServiceImpl, MessageService, MessageServiceImpl, ProfileService, ProfileServiceImpl, SchemaService, SchemaServiceImpl, SiweService, SiweServiceImpl, TemplateService, TemplateServiceImpl, UserService, UserServiceImpl, VerificationService, VerificationServiceImpl, WalletService, WalletServiceImpl};
+use repositories::{Uuid, payout_address_message};
 
 mod repositories;
 mod services;
@@ -14,31 +16,93 @@ fn init() {
     let calling_principal = ic_cdk::caller();
 
     //add principal of canister creator as admin
-    UserServiceImpl::default().create_user(calling_principal, User{role: Role::Admin}).unwrap();
+    UserServiceImpl::default().grant_role(calling_principal, Role::Admin, calling_principal).unwrap();
+
+    start_anchor_timer();
+}
+
+#[post_upgrade]
+fn post_upgrade() {
+    //contracts stored before multi-party contracts are rewritten and indexed once
+    ContractServiceImpl::default().migrate_legacy_contracts();
+
+    //the certified tree lives on the heap and certified data is not kept across upgrades
+    ContractServiceImpl::default().certify_contracts();
+    ICRC3ServiceImpl::default().certify_tip();
+
+    //timers are not kept across upgrades either
+    start_anchor_timer();
 }
 
+/// Interval between two anchoring runs of signed contracts on the EVM chain
+const ANCHOR_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+fn start_anchor_timer() {
+    ic_cdk_timers::set_timer_interval(ANCHOR_INTERVAL, || {
+        // failed EVM calls are recorded in the audit log by the anchor service
+        ic_cdk::spawn(async {
+            let _ = AnchorServiceImpl::default().with_wallet(WalletServiceImpl::new(true)).anchor_pending_contracts().await;
+        })
+    });
+}
+
+/// Grant a role to a principal in addition to the roles it already has
 #[ic_cdk::update]
 fn add_permission(principal: Principal, role: Role) -> Result<(), ApiError> {
     let caller = ic_cdk::caller();
-    AccessControlServiceImpl::default().assert_principal_is_admin(&caller)?;
+    AccessControlServiceImpl::default().assert_permission(&caller, Permission::UsersManage)?;
 
-    UserServiceImpl::default().create_user(principal, User{role})
+    UserServiceImpl::default().grant_role(principal, role, caller)
 }
 
+/// Revoke a role from a principal, or all of its roles if none is given
 #[ic_cdk::update]
-fn remove_permission(principal: Principal) -> Result<(), ApiError> {
+fn remove_permission(principal: Principal, role: Option<Role>) -> Result<(), ApiError> {
     let caller = ic_cdk::caller();
-    AccessControlServiceImpl::default().assert_principal_is_admin(&caller)?;
+    AccessControlServiceImpl::default().assert_permission(&caller, Permission::UsersManage)?;
 
-    UserServiceImpl::default().remove_user(&principal)
+    UserServiceImpl::default().revoke_role(&principal, role, caller)
 }
 
+/// Replace the roles of a principal with a single role
 #[ic_cdk::update]
 fn update_permission(principal: Principal, role: Role) -> Result<(), ApiError> {
     let caller = ic_cdk::caller();
-    AccessControlServiceImpl::default().assert_principal_is_admin(&caller)?;
+    AccessControlServiceImpl::default().assert_permission(&caller, Permission::UsersManage)?;
 
-    UserServiceImpl::default().update_user(principal, User{role})
+    UserServiceImpl::default().set_roles(principal, vec![role], caller)
+}
+
+/// Replace the roles of a principal
+#[ic_cdk::update]
+fn set_roles(principal: Principal, roles: Vec<Role>) -> Result<(), ApiError> {
+    let caller = ic_cdk::caller();
+    AccessControlServiceImpl::default().assert_permission(&caller, Permission::UsersManage)?;
+
+    UserServiceImpl::default().set_roles(principal, roles, caller)
+}
+
+/// Make a principal admin again, e.g. after the last admin lost access to its identity.
+/// Only controllers of the canister can call this endpoint.
+#[ic_cdk::update]
+fn restore_admin(principal: Principal) -> Result<(), ApiError> {
+    let caller = ic_cdk::caller();
+    AccessControlServiceImpl::default().assert_principal_is_controller(&caller)?;
+
+    UserServiceImpl::default().restore_admin(principal, caller)
+}
+
+/// Query the permissions granted to a principal by its roles.
+/// Principals can query their own permissions, user managers those of any principal.
+#[ic_cdk::query]
+fn get_permissions(principal: Option<Principal>) -> Result<Vec<Permission>, ApiError> {
+    let caller = ic_cdk::caller();
+    let principal = principal.unwrap_or(caller);
+    if caller != principal {
+        AccessControlServiceImpl::default().assert_permission(&caller, Permission::UsersManage)?;
+    }
+
+    Ok(UserServiceImpl::default().get_permissions(&principal))
 }
 
 #[ic_cdk::query]
@@ -46,32 +110,456 @@ fn get_users() -> Vec<(Principal, User)> {
     UserServiceImpl::default().list_users()
 }
 
-/// Create a new unsigned contract in storage
+#[ic_cdk::query]
+fn get_config() -> Config {
+    ConfigServiceImpl::default().get_config()
+}
+
+#[ic_cdk::update]
+fn update_config(config: Config) -> Result<(), ApiError> {
+    let caller = ic_cdk::caller();
+    AccessControlServiceImpl::default().assert_permission(&caller, Permission::ConfigWrite)?;
+
+    ConfigServiceImpl::default().update_config(config, caller)
+}
+
+/// Register a new JSON Schema that contract json has to match from now on
+#[ic_cdk::update]
+fn register_contract_schema(schema_json: String) -> Result<u32, ApiError> {
+    let caller = ic_cdk::caller();
+    AccessControlServiceImpl::default().assert_permission(&caller, Permission::SchemasManage)?;
+
+    SchemaServiceImpl::default().register_contract_schema(schema_json, caller)
+}
+
+/// Query a contract schema by version, defaulting to the active one
+#[ic_cdk::query]
+fn get_contract_schema(version: Option<u32>) -> Option<ContractSchema> {
+    SchemaServiceImpl::default().get_contract_schema(version)
+}
+
+#[ic_cdk::query]
+fn list_contract_schemas() -> Vec<ContractSchema> {
+    SchemaServiceImpl::default().list_contract_schemas()
+}
+
+/// Register a new contract template, returning its id
+#[ic_cdk::update]
+fn register_contract_template(name: String, schema_json: String, body_json: String, defaults_json: Option<String>) -> Result<Uuid, ApiError> {
+    let caller = ic_cdk::caller();
+    AccessControlServiceImpl::default().assert_permission(&caller, Permission::SchemasManage)?;
+
+    TemplateServiceImpl::default().register_template(name, schema_json, body_json, defaults_json, caller)
+}
+
+/// Register a new version of an existing contract template
+#[ic_cdk::update]
+fn update_contract_template(template_id: String, name: String, schema_json: String, body_json: String, defaults_json: Option<String>) -> Result<u32, ApiError> {
+    let caller = ic_cdk::caller();
+    AccessControlServiceImpl::default().assert_permission(&caller, Permission::SchemasManage)?;
+
+    TemplateServiceImpl::default().update_template(template_id, name, schema_json, body_json, defaults_json, caller)
+}
+
+/// Query a contract template by version, defaulting to its latest version
+#[ic_cdk::query]
+fn get_contract_template(template_id: String, version: Option<u32>) -> Option<ContractTemplate> {
+    TemplateServiceImpl::default().get_template(template_id, version)
+}
+
+/// List the latest version of every contract template
+#[ic_cdk::query]
+fn list_contract_templates() -> Vec<ContractTemplate> {
+    TemplateServiceImpl::default().list_templates()
+}
+
+/// Create a new unsigned contract in storage.
+/// Buyer and seller must be different principals: since multi-party contracts a principal
+/// can only be listed once, so a contract with the same buyer and seller is rejected.
+#[ic_cdk::update]
+fn create_contract(contract_json: String, buyer: Principal, seller: Principal) -> Result<Uuid, ApiError> {
+    let caller = ic_cdk::caller();
+    AccessControlServiceImpl::default().assert_permission(&caller, Permission::ContractsCreate)?;
+
+    ContractServiceImpl::default().create_contract(contract_json, buyer, seller, caller)
+}
+
+/// Create a new unsigned contract from the latest version of a template.
+/// params is a json object with values for the template placeholders; params matching no placeholder are rejected.
+/// The template id and version are recorded on the contract.
+#[ic_cdk::update]
+fn create_contract_from_template(template_id: String, params: String, buyer: Principal, seller: Principal) -> Result<Uuid, ApiError> {
+    let caller = ic_cdk::caller();
+    AccessControlServiceImpl::default().assert_permission(&caller, Permission::ContractsCreate)?;
+
+    let (contract_json, template) = TemplateServiceImpl::default().render_template(template_id, params)?;
+    ContractServiceImpl::default().create_contract_from_template(contract_json, template, buyer, seller, caller)
+}
+
+/// Create a new unsigned contract with any number of signatories and a custom signature quorum.
+/// evm_signatories are identified by the Ethereum address they sign with, see sign_contract.
+#[ic_cdk::update]
+fn create_multi_party_contract(contract_json: String, signatories: Vec<(Principal, SignatoryRole)>, quorum: SignatureQuorum, evm_signatories: Option<Vec<(String, SignatoryRole)>>) -> Result<Uuid, ApiError> {
+    let caller = ic_cdk::caller();
+    AccessControlServiceImpl::default().assert_permission(&caller, Permission::ContractsCreate)?;
+
+    ContractServiceImpl::default().create_multi_party_contract(contract_json, signatories, evm_signatories.unwrap_or_default(), quorum, caller)
+}
+
+/// Create a new unsigned contract whose json is encrypted client side.
+/// The client picks a random key_id of 16 to 64 bytes and IBE-encrypts the json to the public key returned by
+/// get_contract_encryption_public_key, using as identity the caller's principal bytes followed by key_id.
+/// Signatories decrypt it with the key from get_contract_encryption_key.
+/// content_hash is the hash of the plaintext json.
 #[ic_cdk::update]
-fn create_contract(contract_json: String, buyer: Principal, seller: Principal) -> Uuid {
-    ContractServiceImpl::default().create_contract(contract_json, buyer, seller)
+fn create_encrypted_contract(ciphertext: String, content_hash: String, key_id: Vec<u8>, buyer: Principal, seller: Principal) -> Result<Uuid, ApiError> {
+    let caller = ic_cdk::caller();
+    AccessControlServiceImpl::default().assert_permission(&caller, Permission::ContractsCreate)?;
+
+    ContractServiceImpl::default().create_encrypted_contract(ciphertext, content_hash, key_id, buyer, seller, caller)
+}
+
+/// Propose an amended contract json, invalidating the existing signatures.
+/// Fully signed contracts cannot be amended.
+#[ic_cdk::update]
+fn amend_contract(contract_id: String, contract_json: String) -> Result<u32, ApiError> {
+    let caller = ic_cdk::caller();
+
+    ContractServiceImpl::default().amend_contract(contract_id, contract_json, caller)
+}
+
+/// Propose an amended ciphertext for an encrypted contract, invalidating the existing signatures.
+/// Fully signed contracts cannot be amended.
+#[ic_cdk::update]
+fn amend_encrypted_contract(contract_id: String, ciphertext: String, content_hash: String) -> Result<u32, ApiError> {
+    let caller = ic_cdk::caller();
+
+    ContractServiceImpl::default().amend_encrypted_contract(contract_id, ciphertext, content_hash, caller)
 }
 
 // Sign a contract
+// content_hash is the hash of the contract json shown to the user
+// delegation_origin is the origin the frontend obtained the caller's delegation from, if any
+// evm_signature signs the contract on behalf of a signatory identified by its Ethereum address instead of the caller
 #[ic_cdk::update]
-async fn sign_contract(contract_id: String) -> Result<(), ApiError> {
+async fn sign_contract(contract_id: String, content_hash: String, delegation_origin: Option<String>, evm_signature: Option<EvmSignature>) -> Result<(), ApiError> {
     let caller = ic_cdk::caller();
 
-    ContractServiceImpl::default()
-    .with_wallet(WalletServiceImpl::new(true))
-    .sign_contract(contract_id, caller)
+    let contract_service = ContractServiceImpl::default().with_wallet(WalletServiceImpl::new(true));
+    match evm_signature {
+        Some(evm_signature) => contract_service.sign_contract_with_evm(contract_id, content_hash, evm_signature, caller),
+        None => contract_service.sign_contract(contract_id, content_hash, caller, delegation_origin),
+    }
+}
+
+/// Withdraw a signature before the counterparty has signed
+#[ic_cdk::update]
+fn revoke_signature(contract_id: String) -> Result<(), ApiError> {
+    let caller = ic_cdk::caller();
+
+    ContractServiceImpl::default().revoke_signature(contract_id, caller)
 }
 
-/// Query a contract by its ID
+/// Query a contract by its ID with the profiles of its signatories, restricted to its signatories and authorized roles
 #[ic_cdk::query]
-fn get_contract(contract_id: String) -> Option<Contract> {
-    ContractServiceImpl::default().get_contract(contract_id)
+fn get_contract(contract_id: String) -> Result<ContractDetails, ApiError> {
+    let caller = ic_cdk::caller();
+
+    let contract = ContractServiceImpl::default().get_contract(contract_id)?;
+    AccessControlServiceImpl::default().assert_can_read_contract(&caller, &contract)?;
+
+    let profiles = ProfileServiceImpl::default().get_signatory_profiles(&contract);
+    Ok(ContractDetails { contract, profiles })
+}
+
+/// Query a contract with a certificate and a witness for contracts/<contract id> in the certified tree,
+/// so the frontend can verify the response. Restricted like get_contract.
+#[ic_cdk::query]
+fn get_certified_contract(contract_id: String) -> Result<CertifiedContract, ApiError> {
+    let caller = ic_cdk::caller();
+
+    let contract_service = ContractServiceImpl::default();
+    let certified = contract_service.get_certified_contract(contract_id)?;
+    AccessControlServiceImpl::default().assert_can_read_contract(&caller, &certified.contract)?;
+
+    Ok(certified)
+}
+
+/// Query the signature status of a contract with a certificate and a witness for signed/<contract id>
+#[ic_cdk::query]
+fn is_signed_certified(contract_id: String) -> Result<CertifiedSignatureStatus, ApiError> {
+    let caller = ic_cdk::caller();
+
+    let contract_service = ContractServiceImpl::default();
+    let contract = contract_service.get_contract(contract_id.clone())?;
+    AccessControlServiceImpl::default().assert_can_read_contract(&caller, &contract)?;
+
+    contract_service.get_certified_signature_status(contract_id)
+}
+
+/// Query the public summary of a contract, if enabled in the config
+#[ic_cdk::query]
+fn get_contract_summary(contract_id: String) -> Result<ContractSummary, ApiError> {
+    let config = ConfigServiceImpl::default().get_config();
+
+    ContractServiceImpl::default().get_contract_summary(contract_id, &config.public_summary)
+}
+
+/// Query the version history of a contract
+#[ic_cdk::query]
+fn get_contract_versions(contract_id: String) -> Result<Vec<ContractVersion>, ApiError> {
+    let caller = ic_cdk::caller();
+
+    let contract_service = ContractServiceImpl::default();
+    let contract = contract_service.get_contract(contract_id.clone())?;
+    AccessControlServiceImpl::default().assert_can_read_contract(&caller, &contract)?;
+
+    contract_service.get_contract_versions(contract_id)
+}
+
+/// Start the upload of a document attached to a contract.
+/// sha256 is the hex encoded hash of the whole file, verified when the upload is finalized.
+/// Uploads that are not finalized within a day are discarded.
+#[ic_cdk::update]
+fn create_attachment(contract_id: String, file_name: String, mime_type: String, size: u64, sha256: String) -> Result<Uuid, ApiError> {
+    let caller = ic_cdk::caller();
+
+    AttachmentServiceImpl::default().create_attachment(contract_id, file_name, mime_type, size, sha256, caller)
+}
+
+/// Upload a chunk of an attachment, chunks are MAX_ATTACHMENT_CHUNK_SIZE bytes except for the last one
+#[ic_cdk::update]
+fn upload_attachment_chunk(contract_id: String, attachment_id: String, index: u32, data: Vec<u8>) -> Result<(), ApiError> {
+    let caller = ic_cdk::caller();
+
+    AttachmentServiceImpl::default().upload_attachment_chunk(contract_id, attachment_id, index, data, caller)
+}
+
+/// Verify the uploaded chunks of an attachment against its sha256 and make it available for download
+#[ic_cdk::update]
+fn finalize_attachment(contract_id: String, attachment_id: String) -> Result<(), ApiError> {
+    let caller = ic_cdk::caller();
+
+    AttachmentServiceImpl::default().finalize_attachment(contract_id, attachment_id, caller)
+}
+
+/// Cancel the upload of an attachment that has not been finalized, only the uploader may cancel it
+#[ic_cdk::update]
+fn abort_attachment(contract_id: String, attachment_id: String) -> Result<(), ApiError> {
+    let caller = ic_cdk::caller();
+
+    AttachmentServiceImpl::default().abort_attachment(contract_id, attachment_id, caller)
+}
+
+/// Query the metadata of an attachment, restricted like the contract itself
+#[ic_cdk::query]
+fn get_attachment(contract_id: String, attachment_id: String) -> Result<Attachment, ApiError> {
+    let caller = ic_cdk::caller();
+
+    let contract = ContractServiceImpl::default().get_contract(contract_id.clone())?;
+    AccessControlServiceImpl::default().assert_can_read_contract(&caller, &contract)?;
+
+    AttachmentServiceImpl::default().get_attachment(contract_id, attachment_id)
+}
+
+/// List the attachments of a contract, restricted like the contract itself
+#[ic_cdk::query]
+fn list_attachments(contract_id: String) -> Result<Vec<Attachment>, ApiError> {
+    let caller = ic_cdk::caller();
+
+    let contract = ContractServiceImpl::default().get_contract(contract_id.clone())?;
+    AccessControlServiceImpl::default().assert_can_read_contract(&caller, &contract)?;
+
+    AttachmentServiceImpl::default().list_attachments(contract_id)
+}
+
+/// Download a chunk of a finalized attachment, restricted like the contract itself
+#[ic_cdk::query]
+fn get_attachment_chunk(contract_id: String, attachment_id: String, index: u32) -> Result<Vec<u8>, ApiError> {
+    let caller = ic_cdk::caller();
+
+    let contract = ContractServiceImpl::default().get_contract(contract_id.clone())?;
+    AccessControlServiceImpl::default().assert_can_read_contract(&caller, &contract)?;
+
+    AttachmentServiceImpl::default().get_attachment_chunk(contract_id, attachment_id, index)
+}
+
+/// Query the audit log entries of a contract from the start index, restricted like the contract itself
+#[ic_cdk::query]
+fn get_contract_audit_log(contract_id: String, start: Option<u64>, limit: Option<u32>) -> Result<AuditPage, ApiError> {
+    let caller = ic_cdk::caller();
+
+    let contract = ContractServiceImpl::default().get_contract(contract_id.clone())?;
+    AccessControlServiceImpl::default().assert_can_read_contract(&caller, &contract)?;
+
+    AuditServiceImpl::default().get_contract_audit_log(contract_id, start, limit)
+}
+
+/// Query the audit log entries a principal acted in or was affected by.
+/// Principals can query their own entries, auditors those of any principal.
+#[ic_cdk::query]
+fn get_principal_audit_log(principal: Principal, start: Option<u64>, limit: Option<u32>) -> Result<AuditPage, ApiError> {
+    let caller = ic_cdk::caller();
+    if caller != principal {
+        AccessControlServiceImpl::default().assert_permission(&caller, Permission::AuditRead)?;
+    }
+
+    Ok(AuditServiceImpl::default().get_principal_audit_log(principal, start, limit))
+}
+
+/// Query the profile of a principal.
+/// Principals can query their own profile, auditors that of any principal.
+#[ic_cdk::query]
+fn get_profile(principal: Option<Principal>) -> Result<Profile, ApiError> {
+    let caller = ic_cdk::caller();
+    let principal = principal.unwrap_or(caller);
+    if caller != principal {
+        AccessControlServiceImpl::default().assert_permission(&caller, Permission::AuditRead)?;
+    }
+
+    ProfileServiceImpl::default().get_profile(principal)
+}
+
+/// Create or update the caller's profile
+#[ic_cdk::update]
+fn update_profile(update: ProfileUpdate) -> Result<(), ApiError> {
+    let caller = ic_cdk::caller();
+    AccessControlServiceImpl::default().assert_principal_not_anonymous(&caller)?;
+
+    ProfileServiceImpl::default().update_profile(update, caller)
+}
+
+/// Get the message the caller's payout address has to sign with personal_sign to be added to its profile
+#[ic_cdk::query]
+fn get_payout_address_message(address: String) -> Result<String, ApiError> {
+    let caller = ic_cdk::caller();
+    AccessControlServiceImpl::default().assert_principal_not_anonymous(&caller)?;
+
+    let address = address.parse::<Address>().map_err(|e| ApiError::invalid_argument(&format!("Invalid Ethereum address: {}", e)))?;
+    Ok(payout_address_message(&address.to_string(), &caller))
+}
+
+/// Add a payout address to the caller's profile, signature is the address' signature of get_payout_address_message
+#[ic_cdk::update]
+fn add_payout_address(address: String, signature: String) -> Result<(), ApiError> {
+    let caller = ic_cdk::caller();
+    AccessControlServiceImpl::default().assert_principal_not_anonymous(&caller)?;
+
+    ProfileServiceImpl::default().add_payout_address(address, signature, caller)
+}
+
+#[ic_cdk::update]
+fn remove_payout_address(address: String) -> Result<(), ApiError> {
+    let caller = ic_cdk::caller();
+
+    ProfileServiceImpl::default().remove_payout_address(address, caller)
+}
+
+/// Query the verification record of a principal.
+/// Principals can query their own record, compliance officers and admins that of any principal.
+#[ic_cdk::query]
+fn get_verification(principal: Option<Principal>) -> Result<Verification, ApiError> {
+    let caller = ic_cdk::caller();
+    let principal = principal.unwrap_or(caller);
+    if caller != principal {
+        AccessControlServiceImpl::default().assert_permission(&caller, Permission::VerificationsRead)?;
+    }
+
+    VerificationServiceImpl::default().get_verification(principal)
+}
+
+/// Record the outcome of a KYC check for a principal, expires_at is an IC timestamp in nanoseconds
+#[ic_cdk::update]
+fn set_verification(principal: Principal, level: VerificationLevel, provider_reference: String, expires_at: Option<u64>) -> Result<(), ApiError> {
+    let caller = ic_cdk::caller();
+    AccessControlServiceImpl::default().assert_permission(&caller, Permission::VerificationsManage)?;
+
+    VerificationServiceImpl::default().set_verification(principal, level, provider_reference, expires_at, caller)
+}
+
+#[ic_cdk::update]
+fn remove_verification(principal: Principal) -> Result<(), ApiError> {
+    let caller = ic_cdk::caller();
+    AccessControlServiceImpl::default().assert_permission(&caller, Permission::VerificationsManage)?;
+
+    VerificationServiceImpl::default().remove_verification(principal, caller)
+}
+
+/// Add principals and Ethereum addresses with the reason for listing them to the denylist.
+/// Denylisted parties cannot sign contracts, be paid or receive payments.
+#[ic_cdk::update]
+fn import_denylist(entries: Vec<(DenylistSubject, String)>) -> Result<u32, ApiError> {
+    let caller = ic_cdk::caller();
+    AccessControlServiceImpl::default().assert_permission(&caller, Permission::DenylistManage)?;
+
+    DenylistServiceImpl::default().import_denylist(entries, caller)
+}
+
+#[ic_cdk::update]
+fn remove_from_denylist(subjects: Vec<DenylistSubject>) -> Result<u32, ApiError> {
+    let caller = ic_cdk::caller();
+    AccessControlServiceImpl::default().assert_permission(&caller, Permission::DenylistManage)?;
+
+    DenylistServiceImpl::default().remove_from_denylist(subjects, caller)
+}
+
+#[ic_cdk::query]
+fn list_denylist() -> Result<Vec<(DenylistSubject, DenylistEntry)>, ApiError> {
+    let caller = ic_cdk::caller();
+    AccessControlServiceImpl::default().assert_permission(&caller, Permission::DenylistManage)?;
+
+    Ok(DenylistServiceImpl::default().list_denylist())
+}
+
+/// Post a message to the thread of a contract, optionally referencing one of its attachments
+#[ic_cdk::update]
+fn post_message(contract_id: String, text: String, attachment_id: Option<String>) -> Result<u64, ApiError> {
+    let caller = ic_cdk::caller();
+
+    MessageServiceImpl::default().post_message(contract_id, text, attachment_id, caller)
+}
+
+/// Query the message thread of a contract from the start index, restricted to its signatories
+#[ic_cdk::query]
+fn list_messages(contract_id: String, start: Option<u64>, limit: Option<u32>) -> Result<MessagePage, ApiError> {
+    let caller = ic_cdk::caller();
+
+    MessageServiceImpl::default().list_messages(contract_id, start, limit, caller)
+}
+
+/// List the contracts the caller is a signatory of, with optional filters, sorting and cursor pagination
+#[ic_cdk::query]
+fn list_contracts(request: ListContractsRequest) -> Result<ContractPage, ApiError> {
+    let caller = ic_cdk::caller();
+    AccessControlServiceImpl::default().assert_principal_not_anonymous(&caller)?;
+
+    ContractServiceImpl::default().list_contracts(request, caller)
 }
 
 /// query signature status of a contract
 #[ic_cdk::query]
 fn is_signed(contract_id: String) -> Result<bool, ApiError> {
-    ContractServiceImpl::default().is_signed(contract_id)
+    let caller = ic_cdk::caller();
+
+    let contract = ContractServiceImpl::default().get_contract(contract_id)?;
+    AccessControlServiceImpl::default().assert_can_read_contract(&caller, &contract)?;
+
+    Ok(contract.is_signed())
+}
+
+/// Get the vetKey of an encrypted contract, encrypted under the caller's transport public key.
+/// Only available to the signatories of the contract.
+#[ic_cdk::update]
+async fn get_contract_encryption_key(contract_id: String, transport_public_key: Vec<u8>) -> Result<Vec<u8>, ApiError> {
+    let caller = ic_cdk::caller();
+
+    ContractServiceImpl::default().get_contract_encryption_key(contract_id, transport_public_key, caller).await
+}
+
+/// Get the vetKD public key used to encrypt contracts
+#[ic_cdk::update]
+async fn get_contract_encryption_public_key() -> Result<Vec<u8>, ApiError> {
+    ContractServiceImpl::default().get_encryption_public_key().await
 }
 
 #[ic_cdk::update]
@@ -91,16 +579,60 @@ async fn get_balance_usdc(address: Option<String>) -> Result<String, ApiError> {
     WalletServiceImpl::new(true).get_balance_usdc(address).await
 }
 
+/// Anchor the signed contracts waiting for it now instead of on the next timer run.
+/// Confirms the root submitted by the previous run, then submits a root for the contracts still pending.
+/// Returns the number of contracts whose anchoring was confirmed.
+#[ic_cdk::update]
+async fn anchor_pending_contracts() -> Result<u32, ApiError> {
+    let caller = ic_cdk::caller();
+    AccessControlServiceImpl::default().assert_permission(&caller, Permission::ContractsAnchor)?;
+
+    AnchorServiceImpl::default()
+    .with_wallet(WalletServiceImpl::new(true))
+    .anchor_pending_contracts()
+    .await
+}
+
+/// Get the current state of a contract as EIP-712 typed data signed by the canister's attestation key,
+/// so it can be handed to third parties and verified without calling the canister
+#[ic_cdk::update]
+async fn attest_contract(contract_id: String) -> Result<ContractAttestation, ApiError> {
+    let caller = ic_cdk::caller();
+
+    let contract = ContractServiceImpl::default().get_contract(contract_id.clone())?;
+    AccessControlServiceImpl::default().assert_can_read_contract(&caller, &contract)?;
+
+    AttestationServiceImpl::default()
+    .with_wallet(WalletServiceImpl::new(true))
+    .attest_contract(contract_id)
+    .await
+}
+
+/// Get the public key and Ethereum address attestations are signed with
+#[ic_cdk::update]
+async fn get_attestation_public_key() -> Result<AttestationPublicKey, ApiError> {
+    AttestationServiceImpl::default()
+    .with_wallet(WalletServiceImpl::new(true))
+    .get_attestation_public_key()
+    .await
+}
+
+/// Pay the seller of a signed contract.
+/// Without an address the payment goes to the seller's first verified payout address.
 #[ic_cdk::update]
-async fn issue_payment(contract_id: String, seller_principal: Principal, address: String, amount: u64) -> Result<(), ApiError> {
+async fn issue_payment(contract_id: String, seller_principal: Principal, address: Option<String>, amount: u64) -> Result<(), ApiError> {
     let caller = ic_cdk::caller();
-    AccessControlServiceImpl::default().assert_principal_is_frontend(&caller)?;
+    AccessControlServiceImpl::default().assert_permission(&caller, Permission::PaymentsIssue)?;
 
+    let address = match address {
+        Some(address) => address,
+        None => ProfileServiceImpl::default().get_payout_address(seller_principal)?,
+    };
     let address = address.parse::<Address>().map_err(|e| ApiError::internal(e.to_string().as_str()))?;
 
     ContractServiceImpl::default()
     .with_wallet(WalletServiceImpl::new(true))
-    .issue_payment(contract_id, seller_principal, address, amount).await
+    .issue_payment(contract_id, seller_principal, address, amount, caller).await
 }
 
 // #[ic_cdk::update]
@@ -123,9 +655,63 @@ async fn issue_payment(contract_id: String, seller_principal: Principal, address
 //     services::wallet_service::transfer_usdc().await
 // }
 
+/// Query blocks of the ICRC-3 log of contract and payment events
+#[ic_cdk::query]
+fn icrc3_get_blocks(args: Vec<GetBlocksArgs>) -> GetBlocksResult {
+    ICRC3ServiceImpl::default().get_blocks(args)
+}
+
+/// Certificate for the index and hash of the last block of the ICRC-3 log
+#[ic_cdk::query]
+fn icrc3_get_tip_certificate() -> Option<ICRC3DataCertificate> {
+    ICRC3ServiceImpl::default().get_tip_certificate().unwrap_or_else(|e| ic_cdk::trap(e.message()))
+}
+
+/// Blocks are never archived, so there are no archive canisters
+#[ic_cdk::query]
+fn icrc3_get_archives(_args: GetArchivesArgs) -> Vec<ICRC3ArchiveInfo> {
+    vec![]
+}
+
+#[ic_cdk::query]
+fn icrc3_supported_block_types() -> Vec<SupportedBlockType> {
+    ICRC3ServiceImpl::default().supported_block_types()
+}
+
 #[ic_cdk::query]
 async fn get_principal() -> Principal {
     ic_cdk::caller()
 }
 
+/// Prepare the Sign-In with Ethereum message an address has to sign to log in, and its nonce
+#[ic_cdk::update]
+fn siwe_prepare_login(address: String) -> Result<SiwePreparedLogin, ApiError> {
+    SiweServiceImpl::default().prepare_login(address)
+}
+
+/// Log in with the signature of the SIWE message prepared with nonce, delegating to session_key.
+/// Contracts listing the address as signatory can then be used with the resulting principal.
+#[ic_cdk::update]
+fn siwe_login(signature: String, address: String, session_key: ByteBuf, nonce: String) -> Result<SiweLoginDetails, ApiError> {
+    SiweServiceImpl::default().login(signature, address, session_key, nonce)
+}
+
+/// Get the delegation signed by siwe_login
+#[ic_cdk::query]
+fn siwe_get_delegation(address: String, session_key: ByteBuf, expiration: u64) -> Result<SignedDelegation, ApiError> {
+    SiweServiceImpl::default().get_delegation(address, session_key, expiration)
+}
+
+/// Query the principal an Ethereum address logged in as
+#[ic_cdk::query]
+fn siwe_get_principal(address: String) -> Result<Principal, ApiError> {
+    SiweServiceImpl::default().get_principal(address)
+}
+
+/// Query the Ethereum address behind a principal obtained with SIWE
+#[ic_cdk::query]
+fn siwe_get_address(principal: Principal) -> Result<String, ApiError> {
+    SiweServiceImpl::default().get_address(principal)
+}
+
 ic_cdk::export_candid!();
\ No newline at end of file
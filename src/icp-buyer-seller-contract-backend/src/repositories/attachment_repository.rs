@@ -0,0 +1,90 @@
+use std::cell::RefCell;
+
+use crate::repositories::{Attachment, AttachmentStatus, Uuid};
+use super::{init_attachment_chunks, init_attachments, AttachmentChunkMemory, AttachmentMemory};
+
+
+pub trait AttachmentRepository {
+    fn add_attachment(&self, attachment: Attachment);
+    fn get_attachment(&self, contract_id: Uuid, attachment_id: Uuid) -> Option<Attachment>;
+    fn list_attachments(&self, contract_id: Uuid) -> Vec<Attachment>;
+    fn update_attachment_status(&self, contract_id: Uuid, attachment_id: Uuid, status: AttachmentStatus);
+    fn remove_attachment(&self, attachment: &Attachment);
+    fn put_chunk(&self, attachment_id: Uuid, index: u32, data: Vec<u8>);
+    fn get_chunk(&self, attachment_id: Uuid, index: u32) -> Option<Vec<u8>>;
+}
+
+pub struct AttachmentRepositoryImpl;
+
+impl AttachmentRepository for AttachmentRepositoryImpl {
+    fn add_attachment(&self, attachment: Attachment) {
+        STATE.with(|attachments| {
+            attachments
+                .borrow_mut()
+                .insert((attachment.contract_id, attachment.attachment_id), attachment);
+        });
+    }
+
+    fn get_attachment(&self, contract_id: Uuid, attachment_id: Uuid) -> Option<Attachment> {
+        STATE.with(|attachments| attachments.borrow().get(&(contract_id, attachment_id)))
+    }
+
+    fn list_attachments(&self, contract_id: Uuid) -> Vec<Attachment> {
+        STATE.with(|attachments| {
+            attachments
+                .borrow()
+                .range((contract_id, Uuid::MIN)..=(contract_id, Uuid::MAX))
+                .map(|(_, attachment)| attachment)
+                .collect()
+        })
+    }
+
+    fn update_attachment_status(&self, contract_id: Uuid, attachment_id: Uuid, status: AttachmentStatus) {
+        STATE.with(|attachments| {
+            let mut attachments = attachments.borrow_mut();
+            if let Some(mut attachment) = attachments.get(&(contract_id, attachment_id)) {
+                attachment.status = status;
+                attachments.insert((contract_id, attachment_id), attachment);
+            }
+        });
+    }
+
+    /// Remove the metadata of an attachment and every chunk uploaded for it
+    fn remove_attachment(&self, attachment: &Attachment) {
+        STATE.with_borrow_mut(|attachments| {
+            attachments.remove(&(attachment.contract_id, attachment.attachment_id));
+        });
+        CHUNKS.with_borrow_mut(|chunks| {
+            for index in 0..attachment.chunk_count {
+                chunks.remove(&(attachment.attachment_id, index));
+            }
+        });
+    }
+
+    fn put_chunk(&self, attachment_id: Uuid, index: u32, data: Vec<u8>) {
+        CHUNKS.with(|chunks| {
+            chunks.borrow_mut().insert((attachment_id, index), data);
+        });
+    }
+
+    fn get_chunk(&self, attachment_id: Uuid, index: u32) -> Option<Vec<u8>> {
+        CHUNKS.with(|chunks| chunks.borrow().get(&(attachment_id, index)))
+    }
+}
+
+impl AttachmentRepositoryImpl {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for AttachmentRepositoryImpl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+thread_local! {
+    static STATE: RefCell<AttachmentMemory> = RefCell::new(init_attachments());
+    static CHUNKS: RefCell<AttachmentChunkMemory> = RefCell::new(init_attachment_chunks());
+}
\ No newline at end of file
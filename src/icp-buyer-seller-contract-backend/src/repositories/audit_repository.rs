@@ -0,0 +1,91 @@
+use std::cell::RefCell;
+
+use candid::Principal;
+use crate::repositories::{AuditEntry, Uuid};
+use super::{init_audit_by_contract, init_audit_by_principal, init_audit_log, AuditByContractMemory, AuditByPrincipalMemory, AuditLogMemory};
+
+
+pub trait AuditRepository {
+    fn append_entry(&self, entry: AuditEntry) -> u64;
+    fn list_entries_by_contract(&self, contract_id: Uuid, start: u64, limit: usize) -> Vec<AuditEntry>;
+    fn list_entries_by_principal(&self, principal: Principal, start: u64, limit: usize) -> Vec<AuditEntry>;
+}
+
+pub struct AuditRepositoryImpl;
+
+impl AuditRepository for AuditRepositoryImpl {
+    /// Append an entry to the audit log and index it by contract and principals
+    fn append_entry(&self, mut entry: AuditEntry) -> u64 {
+        let index = STATE.with(|log| {
+            let log = log.borrow();
+            entry.index = log.len();
+            log.append(&entry).expect("failed to append to the audit log")
+        });
+
+        if let Some(contract_id) = entry.contract_id {
+            BY_CONTRACT.with(|by_contract| {
+                by_contract.borrow_mut().insert((contract_id, index), ());
+            });
+        }
+
+        BY_PRINCIPAL.with(|by_principal| {
+            let mut by_principal = by_principal.borrow_mut();
+            for principal in entry.principals() {
+                by_principal.insert((principal, index), ());
+            }
+        });
+
+        index
+    }
+
+    fn list_entries_by_contract(&self, contract_id: Uuid, start: u64, limit: usize) -> Vec<AuditEntry> {
+        let indexes: Vec<u64> = BY_CONTRACT.with(|by_contract| {
+            by_contract
+                .borrow()
+                .range((contract_id, start)..=(contract_id, u64::MAX))
+                .take(limit)
+                .map(|((_, index), _)| index)
+                .collect()
+        });
+
+        get_entries(indexes)
+    }
+
+    fn list_entries_by_principal(&self, principal: Principal, start: u64, limit: usize) -> Vec<AuditEntry> {
+        let indexes: Vec<u64> = BY_PRINCIPAL.with(|by_principal| {
+            by_principal
+                .borrow()
+                .range((principal, start)..=(principal, u64::MAX))
+                .take(limit)
+                .map(|((_, index), _)| index)
+                .collect()
+        });
+
+        get_entries(indexes)
+    }
+}
+
+fn get_entries(indexes: Vec<u64>) -> Vec<AuditEntry> {
+    STATE.with(|log| {
+        let log = log.borrow();
+        indexes.into_iter().filter_map(|index| log.get(index)).collect()
+    })
+}
+
+impl AuditRepositoryImpl {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for AuditRepositoryImpl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+thread_local! {
+    static STATE: RefCell<AuditLogMemory> = RefCell::new(init_audit_log());
+    static BY_CONTRACT: RefCell<AuditByContractMemory> = RefCell::new(init_audit_by_contract());
+    static BY_PRINCIPAL: RefCell<AuditByPrincipalMemory> = RefCell::new(init_audit_by_principal());
+}
\ No newline at end of file
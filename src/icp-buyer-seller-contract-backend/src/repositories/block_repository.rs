@@ -0,0 +1,62 @@
+use std::cell::RefCell;
+
+use crate::repositories::{new_block, Hash, ICRC3Value};
+use super::{init_blocks, BlockMemory};
+
+
+pub trait BlockRepository {
+    fn append_block(&self, btype: &str, tx: Vec<(String, ICRC3Value)>) -> (u64, Hash);
+    fn get_block(&self, index: u64) -> Option<ICRC3Value>;
+    fn log_length(&self) -> u64;
+    fn get_tip(&self) -> Option<(u64, Hash)>;
+}
+
+pub struct BlockRepositoryImpl;
+
+impl BlockRepository for BlockRepositoryImpl {
+    /// Append a block chained to the current tip and return its index and hash
+    fn append_block(&self, btype: &str, tx: Vec<(String, ICRC3Value)>) -> (u64, Hash) {
+        let phash = self.get_tip().map(|(_, hash)| hash);
+        let block = new_block(btype, phash, tx);
+        let hash = block.hash();
+
+        let index = STATE.with(|blocks| {
+            blocks.borrow().append(&block).expect("failed to append to the block log")
+        });
+
+        (index, hash)
+    }
+
+    fn get_block(&self, index: u64) -> Option<ICRC3Value> {
+        STATE.with(|blocks| blocks.borrow().get(index))
+    }
+
+    fn log_length(&self) -> u64 {
+        STATE.with(|blocks| blocks.borrow().len())
+    }
+
+    /// Index and hash of the last block
+    fn get_tip(&self) -> Option<(u64, Hash)> {
+        STATE.with(|blocks| {
+            let blocks = blocks.borrow();
+            let index = blocks.len().checked_sub(1)?;
+            blocks.get(index).map(|block| (index, block.hash()))
+        })
+    }
+}
+
+impl BlockRepositoryImpl {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for BlockRepositoryImpl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+thread_local! {
+    static STATE: RefCell<BlockMemory> = RefCell::new(init_blocks());
+}
\ No newline at end of file
@@ -0,0 +1,43 @@
+use std::cell::RefCell;
+
+use crate::repositories::Config;
+use super::{init_config, ApiError, ConfigMemory};
+
+
+pub trait ConfigRepository {
+    fn get_config(&self) -> Config;
+    fn update_config(&self, config: Config) -> Result<(), ApiError>;
+}
+
+pub struct ConfigRepositoryImpl;
+
+impl ConfigRepository for ConfigRepositoryImpl {
+    fn get_config(&self) -> Config {
+        STATE.with_borrow(|config| config.get().clone())
+    }
+
+    fn update_config(&self, config: Config) -> Result<(), ApiError> {
+        STATE.with_borrow_mut(|state| {
+            state
+                .set(config)
+                .map(|_| ())
+                .map_err(|e| ApiError::internal(&format!("Failed to store config: {:?}", e)))
+        })
+    }
+}
+
+impl ConfigRepositoryImpl {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for ConfigRepositoryImpl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+thread_local! {
+    static STATE: RefCell<ConfigMemory> = RefCell::new(init_config());
+}
\ No newline at end of file
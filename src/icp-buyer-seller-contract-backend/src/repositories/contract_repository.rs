@@ -1,37 +1,62 @@
 use std::cell::RefCell;
+use std::ops::Bound;
 
+use alloy::primitives::Address;
 use candid::Principal;
-use crate::repositories::{Contract, Uuid};
-use super::{init_contracts, ContractMemory};
+use crate::repositories::{CertifiedHash, Contract, ContractAttestation, ContractAnchor, ContractCursor, ContractSignature, ContractTerms, ContractVersion, SignatoryId, SignatoryRole, Uuid};
+use super::{init_attestations, init_certified_hashes, init_contract_versions, init_contracts, init_created_participant_contracts, init_evm_participant_contracts, init_participant_contracts, init_pending_anchors, CertifiedHashMemory, ContractMemory, ContractVersionMemory, CreatedParticipantContractMemory, EvmParticipantContractMemory, ParticipantContractMemory, PendingAnchorMemory, AttestationMemory};
 
 
 pub trait ContractRepository {
-    fn create_contract(&self, contract_json: String, buyer: Principal, seller: Principal) -> Uuid;
+    fn create_contract(&self, contract: Contract, created_by: Principal) -> Uuid;
     fn get_contract(&self, contract_id: Uuid) -> Option<Contract>;
-    fn update_contract_signature(&self, contract_id: Uuid, signer: Signer);
+    fn amend_contract(&self, contract_id: Uuid, contract_json: String, content_hash: String, terms: ContractTerms, proposed_by: Principal) -> Option<u32>;
+    fn get_contract_versions(&self, contract_id: Uuid) -> Vec<ContractVersion>;
+    fn update_contract_signature(&self, contract_id: Uuid, signer: SignatoryId, signature: Option<ContractSignature>, actor: Principal);
     fn update_payment_status(&self, contract_id: Uuid, status: bool);
-}
-
-#[derive(Debug)]
-pub enum Signer {
-    Buyer,
-    Seller,
+    fn next_participant_contract(&self, principal: Principal, after: Option<ContractCursor>, descending: bool) -> Option<(ContractCursor, SignatoryRole)>;
+    fn list_contracts(&self) -> Vec<(Uuid, Contract)>;
+    fn list_pending_anchors(&self, limit: usize) -> Vec<Uuid>;
+    fn update_contract_anchor(&self, contract_id: Uuid, anchor: ContractAnchor);
+    fn link_evm_signatory(&self, address: Address, principal: Principal) -> Vec<Uuid>;
+    fn migrate_legacy_contracts(&self) -> usize;
+    fn index_contracts_by_creation(&self) -> usize;
+    fn set_certified_hash(&self, contract_id: Uuid, certified: CertifiedHash);
+    fn list_certified_hashes(&self) -> Vec<(Uuid, CertifiedHash)>;
+    fn get_attestation(&self, contract_id: Uuid) -> Option<ContractAttestation>;
+    fn set_attestation(&self, contract_id: Uuid, attestation: ContractAttestation);
 }
 
 pub struct ContractRepositoryImpl;
 
 impl ContractRepository for ContractRepositoryImpl{
     /// Create a new unsigned contract in storage
-    fn create_contract(&self, contract_json: String, buyer: Principal, seller: Principal) -> Uuid {
+    fn create_contract(&self, contract: Contract, created_by: Principal) -> Uuid {
         let contract_id = Uuid::new();
 
-        let contract = Contract::new(contract_json, buyer, seller);
+        let version = ContractVersion::new(contract.version, contract.contract_json.clone(), contract.content_hash.clone(), created_by);
+        for signatory in contract.signatories.iter() {
+            if let Some(principal) = signatory.principal {
+                Self::index_participant(principal, contract_id, contract.created_at, signatory.role);
+            }
+        }
+        EVM_PARTICIPANTS.with(|participants| {
+            let mut participants = participants.borrow_mut();
+            for signatory in contract.signatories.iter() {
+                if let Some(address) = signatory.evm_address.as_ref().and_then(|address| address.parse::<Address>().ok()) {
+                    participants.insert((address.into_array(), contract_id), ());
+                }
+            }
+        });
         STATE.with(|contracts| {
             contracts.borrow_mut().insert(
                 contract_id.clone(),
                 contract,
             );
         });
+        VERSIONS.with(|versions| {
+            versions.borrow_mut().insert((contract_id, version.version), version);
+        });
 
         contract_id
     }
@@ -41,13 +66,47 @@ impl ContractRepository for ContractRepositoryImpl{
         STATE.with(|contracts| contracts.borrow().get(&contract_id).clone())
     }
 
-    fn update_contract_signature(&self, contract_id: Uuid, signer: Signer) {
+    /// Store a new version of the contract json and reset the signatures
+    fn amend_contract(&self, contract_id: Uuid, contract_json: String, content_hash: String, terms: ContractTerms, proposed_by: Principal) -> Option<u32> {
+        let mut contract = self.get_contract(contract_id)?;
+        let version = contract.amend(contract_json.clone(), content_hash.clone(), terms, proposed_by);
+
+        PENDING_ANCHORS.with(|pending| {
+            pending.borrow_mut().remove(&contract_id);
+        });
+        STATE.with(|contracts| {
+            contracts.borrow_mut().insert(contract_id, contract);
+        });
+        VERSIONS.with(|versions| {
+            versions.borrow_mut().insert(
+                (contract_id, version),
+                ContractVersion::new(version, contract_json, content_hash, proposed_by),
+            );
+        });
+
+        Some(version)
+    }
+
+    /// Query the full version history of a contract, oldest first
+    fn get_contract_versions(&self, contract_id: Uuid) -> Vec<ContractVersion> {
+        VERSIONS.with(|versions| {
+            versions
+                .borrow()
+                .range((contract_id, u32::MIN)..=(contract_id, u32::MAX))
+                .map(|(_, version)| version)
+                .collect()
+        })
+    }
+
+    fn update_contract_signature(&self, contract_id: Uuid, signer: SignatoryId, signature: Option<ContractSignature>, actor: Principal) {
         STATE.with(|contracts| {
             let mut contracts = contracts.borrow_mut();
             if let Some(mut contract) = contracts.get(&contract_id) {
-                match signer {
-                    Signer::Buyer => contract.signatories.buyer.1 = true,
-                    Signer::Seller => contract.signatories.seller.1 = true,
+                contract.set_signature(&signer, signature, actor);
+                if contract.needs_anchor() {
+                    PENDING_ANCHORS.with(|pending| {
+                        pending.borrow_mut().insert(contract_id, ());
+                    });
                 }
                 contracts.insert(contract_id, contract);
             }
@@ -63,12 +122,182 @@ impl ContractRepository for ContractRepositoryImpl{
             }
         });
     }
+
+    /// Query the contract of a principal that follows the cursor in creation order, with the principal's role.
+    /// Each call is a single lookup in the creation index, so pages cost the same wherever they start.
+    fn next_participant_contract(&self, principal: Principal, after: Option<ContractCursor>, descending: bool) -> Option<(ContractCursor, SignatoryRole)> {
+        CREATED_PARTICIPANTS.with_borrow(|participants| {
+            let next = match (after, descending) {
+                (Some(after), false) => participants
+                    .range((Bound::Excluded((principal, after.key())), Bound::Unbounded))
+                    .next(),
+                (None, false) => participants.range((principal, (u64::MIN, Uuid::MIN))..).next(),
+                (Some(after), true) => participants.iter_upper_bound(&(principal, after.key())).next(),
+                (None, true) => participants.iter_upper_bound(&(principal, (u64::MAX, Uuid::MAX))).next(),
+            };
+            next.filter(|((owner, _), _)| *owner == principal)
+                .map(|((_, (created_at, contract_id)), role)| (ContractCursor { created_at, contract_id }, role))
+        })
+    }
+
+    fn list_contracts(&self) -> Vec<(Uuid, Contract)> {
+        STATE.with(|contracts| contracts.borrow().iter().collect())
+    }
+
+    /// Query the ids of signed contracts waiting to be anchored on the EVM chain
+    fn list_pending_anchors(&self, limit: usize) -> Vec<Uuid> {
+        PENDING_ANCHORS.with(|pending| {
+            pending.borrow().iter().take(limit).map(|(contract_id, _)| contract_id).collect()
+        })
+    }
+
+    /// Store the anchor of a contract; it stays pending if it was amended in the meantime
+    fn update_contract_anchor(&self, contract_id: Uuid, anchor: ContractAnchor) {
+        STATE.with(|contracts| {
+            let mut contracts = contracts.borrow_mut();
+            if let Some(mut contract) = contracts.get(&contract_id) {
+                contract.anchor = Some(anchor);
+                if !contract.needs_anchor() {
+                    PENDING_ANCHORS.with(|pending| {
+                        pending.borrow_mut().remove(&contract_id);
+                    });
+                }
+                contracts.insert(contract_id, contract);
+            }
+        });
+    }
+
+    /// Set the principal of the signatories identified by an Ethereum address that have none yet,
+    /// unless the principal already is a signatory of the contract. Returns the ids of the updated contracts.
+    fn link_evm_signatory(&self, address: Address, principal: Principal) -> Vec<Uuid> {
+        let contract_ids: Vec<Uuid> = EVM_PARTICIPANTS.with(|participants| {
+            participants
+                .borrow()
+                .range((address.into_array(), Uuid::MIN)..=(address.into_array(), Uuid::MAX))
+                .map(|((_, contract_id), _)| contract_id)
+                .collect()
+        });
+        let address = address.to_string();
+
+        let mut updated = vec![];
+        for contract_id in contract_ids {
+            let Some(mut contract) = self.get_contract(contract_id) else {
+                continue;
+            };
+            if contract.is_signatory(&principal) {
+                continue;
+            }
+            let Some(signatory) = contract.signatories.iter_mut()
+                .find(|s| s.principal.is_none() && s.evm_address.as_ref() == Some(&address)) else {
+                continue;
+            };
+
+            signatory.principal = Some(principal);
+            Self::index_participant(principal, contract_id, contract.created_at, signatory.role);
+            STATE.with(|contracts| {
+                contracts.borrow_mut().insert(contract_id, contract);
+            });
+            updated.push(contract_id);
+        }
+
+        updated
+    }
+
+    /// Rewrite contracts stored before multi-party contracts in the current format
+    /// and add them to the participant index and version history.
+    /// Only runs while the participant index is empty, i.e. once after upgrading from the two-party canister.
+    fn migrate_legacy_contracts(&self) -> usize {
+        if PARTICIPANTS.with_borrow(|participants| !participants.is_empty()) {
+            return 0;
+        }
+
+        let contracts = self.list_contracts();
+        for (contract_id, contract) in contracts.iter() {
+            for signatory in contract.signatories.iter() {
+                if let Some(principal) = signatory.principal {
+                    Self::index_participant(principal, *contract_id, contract.created_at, signatory.role);
+                }
+            }
+            VERSIONS.with_borrow_mut(|versions| {
+                if !versions.contains_key(&(*contract_id, contract.version)) {
+                    // the creator of legacy contracts was not recorded
+                    let mut version = ContractVersion::new(contract.version, contract.contract_json.clone(), contract.content_hash.clone(), Principal::anonymous());
+                    version.created_at = contract.created_at;
+                    versions.insert((*contract_id, contract.version), version);
+                }
+            });
+            if contract.needs_anchor() {
+                PENDING_ANCHORS.with_borrow_mut(|pending| {
+                    pending.insert(*contract_id, ());
+                });
+            }
+        }
+        STATE.with_borrow_mut(|state| {
+            for (contract_id, contract) in contracts.iter() {
+                state.insert(*contract_id, contract.clone());
+            }
+        });
+
+        contracts.len()
+    }
+
+    /// Fill the creation index from the participant index.
+    /// Only runs while the creation index is empty, i.e. once after upgrading from a canister without it.
+    fn index_contracts_by_creation(&self) -> usize {
+        if CREATED_PARTICIPANTS.with_borrow(|participants| !participants.is_empty()) {
+            return 0;
+        }
+
+        let entries: Vec<((Principal, Uuid), SignatoryRole)> = PARTICIPANTS.with_borrow(|participants| participants.iter().collect());
+        CREATED_PARTICIPANTS.with_borrow_mut(|created| {
+            for ((principal, contract_id), role) in entries.iter() {
+                if let Some(contract) = self.get_contract(*contract_id) {
+                    created.insert((*principal, (contract.created_at, *contract_id)), *role);
+                }
+            }
+        });
+
+        entries.len()
+    }
+
+    /// Persist the hash a contract was last certified with
+    fn set_certified_hash(&self, contract_id: Uuid, certified: CertifiedHash) {
+        CERTIFIED_HASHES.with_borrow_mut(|hashes| {
+            hashes.insert(contract_id, certified);
+        });
+    }
+
+    fn list_certified_hashes(&self) -> Vec<(Uuid, CertifiedHash)> {
+        CERTIFIED_HASHES.with_borrow(|hashes| hashes.iter().collect())
+    }
+
+    /// Query the latest attestation signed for a contract
+    fn get_attestation(&self, contract_id: Uuid) -> Option<ContractAttestation> {
+        ATTESTATIONS.with_borrow(|attestations| attestations.get(&contract_id))
+    }
+
+    /// Replace the latest attestation signed for a contract
+    fn set_attestation(&self, contract_id: Uuid, attestation: ContractAttestation) {
+        ATTESTATIONS.with_borrow_mut(|attestations| {
+            attestations.insert(contract_id, attestation);
+        });
+    }
 }
 
 impl ContractRepositoryImpl {
     pub fn new() -> Self {
         Self
     }
+
+    /// Add a signatory principal to the participant indexes
+    fn index_participant(principal: Principal, contract_id: Uuid, created_at: u64, role: SignatoryRole) {
+        PARTICIPANTS.with_borrow_mut(|participants| {
+            participants.insert((principal, contract_id), role);
+        });
+        CREATED_PARTICIPANTS.with_borrow_mut(|participants| {
+            participants.insert((principal, (created_at, contract_id)), role);
+        });
+    }
 }
 
 impl Default for ContractRepositoryImpl {
@@ -79,4 +308,11 @@ impl Default for ContractRepositoryImpl {
 
 thread_local! {
     static STATE: RefCell<ContractMemory> = RefCell::new(init_contracts());
+    static VERSIONS: RefCell<ContractVersionMemory> = RefCell::new(init_contract_versions());
+    static PARTICIPANTS: RefCell<ParticipantContractMemory> = RefCell::new(init_participant_contracts());
+    static EVM_PARTICIPANTS: RefCell<EvmParticipantContractMemory> = RefCell::new(init_evm_participant_contracts());
+    static CREATED_PARTICIPANTS: RefCell<CreatedParticipantContractMemory> = RefCell::new(init_created_participant_contracts());
+    static CERTIFIED_HASHES: RefCell<CertifiedHashMemory> = RefCell::new(init_certified_hashes());
+    static PENDING_ANCHORS: RefCell<PendingAnchorMemory> = RefCell::new(init_pending_anchors());
+    static ATTESTATIONS: RefCell<AttestationMemory> = RefCell::new(init_attestations());
 }
\ No newline at end of file
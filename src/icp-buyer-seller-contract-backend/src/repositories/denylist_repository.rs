@@ -0,0 +1,77 @@
+use std::cell::RefCell;
+
+use alloy::primitives::Address;
+use candid::Principal;
+use crate::repositories::DenylistEntry;
+use super::{init_denylist_addresses, init_denylist_principals, DenylistAddressMemory, DenylistPrincipalMemory};
+
+
+pub trait DenylistRepository {
+    fn add_principal(&self, principal: Principal, entry: DenylistEntry);
+    fn add_address(&self, address: Address, entry: DenylistEntry);
+    fn remove_principal(&self, principal: &Principal) -> Option<DenylistEntry>;
+    fn remove_address(&self, address: Address) -> Option<DenylistEntry>;
+    fn get_principal(&self, principal: &Principal) -> Option<DenylistEntry>;
+    fn get_address(&self, address: Address) -> Option<DenylistEntry>;
+    fn list_principals(&self) -> Vec<(Principal, DenylistEntry)>;
+    fn list_addresses(&self) -> Vec<(Address, DenylistEntry)>;
+}
+
+pub struct DenylistRepositoryImpl;
+
+impl DenylistRepository for DenylistRepositoryImpl {
+    fn add_principal(&self, principal: Principal, entry: DenylistEntry) {
+        PRINCIPALS.with_borrow_mut(|principals| {
+            principals.insert(principal, entry);
+        });
+    }
+
+    fn add_address(&self, address: Address, entry: DenylistEntry) {
+        ADDRESSES.with_borrow_mut(|addresses| {
+            addresses.insert(address.into_array(), entry);
+        });
+    }
+
+    fn remove_principal(&self, principal: &Principal) -> Option<DenylistEntry> {
+        PRINCIPALS.with_borrow_mut(|principals| principals.remove(principal))
+    }
+
+    fn remove_address(&self, address: Address) -> Option<DenylistEntry> {
+        ADDRESSES.with_borrow_mut(|addresses| addresses.remove(&address.into_array()))
+    }
+
+    fn get_principal(&self, principal: &Principal) -> Option<DenylistEntry> {
+        PRINCIPALS.with_borrow(|principals| principals.get(principal))
+    }
+
+    fn get_address(&self, address: Address) -> Option<DenylistEntry> {
+        ADDRESSES.with_borrow(|addresses| addresses.get(&address.into_array()))
+    }
+
+    fn list_principals(&self) -> Vec<(Principal, DenylistEntry)> {
+        PRINCIPALS.with_borrow(|principals| principals.iter().collect())
+    }
+
+    fn list_addresses(&self) -> Vec<(Address, DenylistEntry)> {
+        ADDRESSES.with_borrow(|addresses| {
+            addresses.iter().map(|(address, entry)| (Address::from(address), entry)).collect()
+        })
+    }
+}
+
+impl DenylistRepositoryImpl {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for DenylistRepositoryImpl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+thread_local! {
+    static PRINCIPALS: RefCell<DenylistPrincipalMemory> = RefCell::new(init_denylist_principals());
+    static ADDRESSES: RefCell<DenylistAddressMemory> = RefCell::new(init_denylist_addresses());
+}
\ No newline at end of file
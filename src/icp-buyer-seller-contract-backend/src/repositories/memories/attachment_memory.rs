@@ -0,0 +1,26 @@
+use ic_stable_structures::StableBTreeMap;
+use super::{Memory, MEMORY_MANAGER, ATTACHMENTS_MEMORY_ID, ATTACHMENT_CHUNKS_MEMORY_ID};
+use crate::repositories::{Attachment, Uuid};
+
+
+/// Attachment metadata keyed by contract, so the attachments of a contract can be listed with a range
+pub type AttachmentMemory = StableBTreeMap<(Uuid, Uuid), Attachment, Memory>;
+
+/// File content keyed by attachment and chunk index
+pub type AttachmentChunkMemory = StableBTreeMap<(Uuid, u32), Vec<u8>, Memory>;
+
+pub fn init_attachments() -> AttachmentMemory {
+    StableBTreeMap::init(get_attachments_memory())
+}
+
+pub fn init_attachment_chunks() -> AttachmentChunkMemory {
+    StableBTreeMap::init(get_attachment_chunks_memory())
+}
+
+fn get_attachments_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(ATTACHMENTS_MEMORY_ID))
+}
+
+fn get_attachment_chunks_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(ATTACHMENT_CHUNKS_MEMORY_ID))
+}
\ No newline at end of file
@@ -0,0 +1,15 @@
+use ic_stable_structures::StableBTreeMap;
+use super::{Memory, MEMORY_MANAGER, ATTESTATIONS_MEMORY_ID};
+use crate::repositories::{ContractAttestation, Uuid};
+
+
+/// Latest attestation signed for every contract, reused until its version or status changes
+pub type AttestationMemory = StableBTreeMap<Uuid, ContractAttestation, Memory>;
+
+pub fn init_attestations() -> AttestationMemory {
+    StableBTreeMap::init(get_attestations_memory())
+}
+
+fn get_attestations_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(ATTESTATIONS_MEMORY_ID))
+}
\ No newline at end of file
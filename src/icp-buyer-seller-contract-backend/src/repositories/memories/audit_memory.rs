@@ -0,0 +1,42 @@
+use candid::Principal;
+use ic_stable_structures::{StableBTreeMap, StableLog};
+use super::{Memory, MEMORY_MANAGER, AUDIT_LOG_INDEX_MEMORY_ID, AUDIT_LOG_DATA_MEMORY_ID, AUDIT_BY_CONTRACT_MEMORY_ID, AUDIT_BY_PRINCIPAL_MEMORY_ID};
+use crate::repositories::{AuditEntry, Uuid};
+
+
+pub type AuditLogMemory = StableLog<AuditEntry, Memory, Memory>;
+
+/// Index of the audit log entries of a contract
+pub type AuditByContractMemory = StableBTreeMap<(Uuid, u64), (), Memory>;
+
+/// Index of the audit log entries a principal acted in or was affected by
+pub type AuditByPrincipalMemory = StableBTreeMap<(Principal, u64), (), Memory>;
+
+pub fn init_audit_log() -> AuditLogMemory {
+    StableLog::init(get_audit_log_index_memory(), get_audit_log_data_memory())
+        .expect("failed to initialize the audit log")
+}
+
+pub fn init_audit_by_contract() -> AuditByContractMemory {
+    StableBTreeMap::init(get_audit_by_contract_memory())
+}
+
+pub fn init_audit_by_principal() -> AuditByPrincipalMemory {
+    StableBTreeMap::init(get_audit_by_principal_memory())
+}
+
+fn get_audit_log_index_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(AUDIT_LOG_INDEX_MEMORY_ID))
+}
+
+fn get_audit_log_data_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(AUDIT_LOG_DATA_MEMORY_ID))
+}
+
+fn get_audit_by_contract_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(AUDIT_BY_CONTRACT_MEMORY_ID))
+}
+
+fn get_audit_by_principal_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(AUDIT_BY_PRINCIPAL_MEMORY_ID))
+}
\ No newline at end of file
@@ -0,0 +1,20 @@
+use ic_stable_structures::StableLog;
+use super::{Memory, MEMORY_MANAGER, BLOCKS_INDEX_MEMORY_ID, BLOCKS_DATA_MEMORY_ID};
+use crate::repositories::ICRC3Value;
+
+
+/// ICRC-3 block log, each block contains the hash of the previous one
+pub type BlockMemory = StableLog<ICRC3Value, Memory, Memory>;
+
+pub fn init_blocks() -> BlockMemory {
+    StableLog::init(get_blocks_index_memory(), get_blocks_data_memory())
+        .expect("failed to initialize the block log")
+}
+
+fn get_blocks_index_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(BLOCKS_INDEX_MEMORY_ID))
+}
+
+fn get_blocks_data_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(BLOCKS_DATA_MEMORY_ID))
+}
\ No newline at end of file
@@ -0,0 +1,15 @@
+use ic_stable_structures::StableBTreeMap;
+use super::{Memory, MEMORY_MANAGER, CERTIFIED_HASHES_MEMORY_ID};
+use crate::repositories::{CertifiedHash, Uuid};
+
+
+/// Hash and signature status of every contract as certified, to rebuild the certified tree after an upgrade
+pub type CertifiedHashMemory = StableBTreeMap<Uuid, CertifiedHash, Memory>;
+
+pub fn init_certified_hashes() -> CertifiedHashMemory {
+    StableBTreeMap::init(get_certified_hashes_memory())
+}
+
+fn get_certified_hashes_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(CERTIFIED_HASHES_MEMORY_ID))
+}
\ No newline at end of file
@@ -0,0 +1,14 @@
+use ic_stable_structures::StableCell;
+use super::{Memory, CONFIG_MEMORY_ID, MEMORY_MANAGER};
+use crate::repositories::Config;
+
+
+pub type ConfigMemory = StableCell<Config, Memory>;
+
+pub fn init_config() -> ConfigMemory {
+    StableCell::init(get_config_memory(), Config::default()).expect("Failed to initialize config memory")
+}
+
+fn get_config_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(CONFIG_MEMORY_ID))
+}
\ No newline at end of file
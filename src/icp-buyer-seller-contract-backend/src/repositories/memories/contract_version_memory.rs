@@ -0,0 +1,14 @@
+use ic_stable_structures::StableBTreeMap;
+use super::{Memory, CONTRACT_VERSIONS_MEMORY_ID, MEMORY_MANAGER};
+use crate::repositories::{ContractVersion, Uuid};
+
+
+pub type ContractVersionMemory = StableBTreeMap<(Uuid, u32), ContractVersion, Memory>;
+
+pub fn init_contract_versions() -> ContractVersionMemory {
+    StableBTreeMap::init(get_contract_versions_memory())
+}
+
+fn get_contract_versions_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(CONTRACT_VERSIONS_MEMORY_ID))
+}
\ No newline at end of file
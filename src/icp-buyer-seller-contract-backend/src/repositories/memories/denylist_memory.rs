@@ -0,0 +1,24 @@
+use candid::Principal;
+use ic_stable_structures::StableBTreeMap;
+use super::{Memory, MEMORY_MANAGER, DENYLIST_ADDRESSES_MEMORY_ID, DENYLIST_PRINCIPALS_MEMORY_ID};
+use crate::repositories::DenylistEntry;
+
+
+pub type DenylistPrincipalMemory = StableBTreeMap<Principal, DenylistEntry, Memory>;
+pub type DenylistAddressMemory = StableBTreeMap<[u8; 20], DenylistEntry, Memory>;
+
+pub fn init_denylist_principals() -> DenylistPrincipalMemory {
+    StableBTreeMap::init(get_denylist_principals_memory())
+}
+
+pub fn init_denylist_addresses() -> DenylistAddressMemory {
+    StableBTreeMap::init(get_denylist_addresses_memory())
+}
+
+fn get_denylist_principals_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(DENYLIST_PRINCIPALS_MEMORY_ID))
+}
+
+fn get_denylist_addresses_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(DENYLIST_ADDRESSES_MEMORY_ID))
+}
\ No newline at end of file
@@ -12,4 +12,30 @@ thread_local! {
 // memory IDs are kept together to ensure that the same ID is not used more than once
 // everything else related to each memory region is kept in the appropriate file
 pub(super) const CONTRACTS_MEMORY_ID: MemoryId = MemoryId::new(0);
-pub(super) const USERS_MEMORY_ID: MemoryId = MemoryId::new(1);
\ No newline at end of file
+pub(super) const USERS_MEMORY_ID: MemoryId = MemoryId::new(1);
+pub(super) const CONTRACT_VERSIONS_MEMORY_ID: MemoryId = MemoryId::new(2);
+pub(super) const SCHEMAS_MEMORY_ID: MemoryId = MemoryId::new(3);
+pub(super) const TEMPLATES_MEMORY_ID: MemoryId = MemoryId::new(4);
+pub(super) const PARTICIPANT_CONTRACTS_MEMORY_ID: MemoryId = MemoryId::new(5);
+pub(super) const CONFIG_MEMORY_ID: MemoryId = MemoryId::new(6);
+pub(super) const ATTACHMENTS_MEMORY_ID: MemoryId = MemoryId::new(7);
+pub(super) const ATTACHMENT_CHUNKS_MEMORY_ID: MemoryId = MemoryId::new(8);
+pub(super) const MESSAGES_MEMORY_ID: MemoryId = MemoryId::new(9);
+pub(super) const AUDIT_LOG_INDEX_MEMORY_ID: MemoryId = MemoryId::new(10);
+pub(super) const AUDIT_LOG_DATA_MEMORY_ID: MemoryId = MemoryId::new(11);
+pub(super) const AUDIT_BY_CONTRACT_MEMORY_ID: MemoryId = MemoryId::new(12);
+pub(super) const AUDIT_BY_PRINCIPAL_MEMORY_ID: MemoryId = MemoryId::new(13);
+pub(super) const BLOCKS_INDEX_MEMORY_ID: MemoryId = MemoryId::new(14);
+pub(super) const BLOCKS_DATA_MEMORY_ID: MemoryId = MemoryId::new(15);
+pub(super) const PENDING_ANCHORS_MEMORY_ID: MemoryId = MemoryId::new(16);
+pub(super) const EVM_PARTICIPANT_CONTRACTS_MEMORY_ID: MemoryId = MemoryId::new(17);
+pub(super) const SIWE_ADDRESS_PRINCIPALS_MEMORY_ID: MemoryId = MemoryId::new(18);
+pub(super) const SIWE_PRINCIPAL_ADDRESSES_MEMORY_ID: MemoryId = MemoryId::new(19);
+pub(super) const PROFILES_MEMORY_ID: MemoryId = MemoryId::new(20);
+pub(super) const VERIFICATIONS_MEMORY_ID: MemoryId = MemoryId::new(21);
+pub(super) const DENYLIST_PRINCIPALS_MEMORY_ID: MemoryId = MemoryId::new(22);
+pub(super) const DENYLIST_ADDRESSES_MEMORY_ID: MemoryId = MemoryId::new(23);
+pub(super) const TEMPLATE_LATEST_VERSIONS_MEMORY_ID: MemoryId = MemoryId::new(24);
+pub(super) const CREATED_PARTICIPANT_CONTRACTS_MEMORY_ID: MemoryId = MemoryId::new(25);
+pub(super) const CERTIFIED_HASHES_MEMORY_ID: MemoryId = MemoryId::new(26);
+pub(super) const ATTESTATIONS_MEMORY_ID: MemoryId = MemoryId::new(27);
\ No newline at end of file
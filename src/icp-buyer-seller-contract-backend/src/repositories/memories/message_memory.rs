@@ -0,0 +1,15 @@
+use ic_stable_structures::StableBTreeMap;
+use super::{Memory, MEMORY_MANAGER, MESSAGES_MEMORY_ID};
+use crate::repositories::{ContractMessage, Uuid};
+
+
+/// Message threads keyed by contract and message index
+pub type MessageMemory = StableBTreeMap<(Uuid, u64), ContractMessage, Memory>;
+
+pub fn init_messages() -> MessageMemory {
+    StableBTreeMap::init(get_messages_memory())
+}
+
+fn get_messages_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(MESSAGES_MEMORY_ID))
+}
\ No newline at end of file
@@ -1,8 +1,40 @@
 mod memory_manager;
+mod attachment_memory;
+mod attestation_memory;
+mod audit_memory;
+mod block_memory;
+mod certified_hash_memory;
+mod config_memory;
 mod contract_memory;
+mod contract_version_memory;
+mod denylist_memory;
+mod message_memory;
+mod participant_memory;
+mod pending_anchor_memory;
+mod profile_memory;
+mod schema_memory;
+mod siwe_memory;
+mod template_memory;
 mod user_memory;
+mod verification_memory;
 
 use memory_manager::*;
 
+pub(super) use attachment_memory::*;
+pub(super) use attestation_memory::*;
+pub(super) use audit_memory::*;
+pub(super) use block_memory::*;
+pub(super) use certified_hash_memory::*;
+pub(super) use config_memory::*;
 pub(super) use contract_memory::*;
-pub(super) use user_memory::*;
\ No newline at end of file
+pub(super) use contract_version_memory::*;
+pub(super) use denylist_memory::*;
+pub(super) use message_memory::*;
+pub(super) use participant_memory::*;
+pub(super) use pending_anchor_memory::*;
+pub(super) use profile_memory::*;
+pub(super) use schema_memory::*;
+pub(super) use siwe_memory::*;
+pub(super) use template_memory::*;
+pub(super) use user_memory::*;
+pub(super) use verification_memory::*;
\ No newline at end of file
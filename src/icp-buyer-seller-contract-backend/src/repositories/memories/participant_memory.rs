@@ -0,0 +1,36 @@
+use candid::Principal;
+use ic_stable_structures::StableBTreeMap;
+use super::{Memory, MEMORY_MANAGER, CREATED_PARTICIPANT_CONTRACTS_MEMORY_ID, EVM_PARTICIPANT_CONTRACTS_MEMORY_ID, PARTICIPANT_CONTRACTS_MEMORY_ID};
+use crate::repositories::{SignatoryRole, Uuid};
+
+
+/// Index of the contracts a principal is a signatory of, with the role it holds
+pub type ParticipantContractMemory = StableBTreeMap<(Principal, Uuid), SignatoryRole, Memory>;
+/// Index of the contracts an Ethereum address is a signatory of
+pub type EvmParticipantContractMemory = StableBTreeMap<([u8; 20], Uuid), (), Memory>;
+/// Index of the contracts a principal is a signatory of, ordered by creation time and id for keyset pagination
+pub type CreatedParticipantContractMemory = StableBTreeMap<(Principal, (u64, Uuid)), SignatoryRole, Memory>;
+
+pub fn init_participant_contracts() -> ParticipantContractMemory {
+    StableBTreeMap::init(get_participant_contracts_memory())
+}
+
+pub fn init_evm_participant_contracts() -> EvmParticipantContractMemory {
+    StableBTreeMap::init(get_evm_participant_contracts_memory())
+}
+
+pub fn init_created_participant_contracts() -> CreatedParticipantContractMemory {
+    StableBTreeMap::init(get_created_participant_contracts_memory())
+}
+
+fn get_participant_contracts_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(PARTICIPANT_CONTRACTS_MEMORY_ID))
+}
+
+fn get_evm_participant_contracts_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(EVM_PARTICIPANT_CONTRACTS_MEMORY_ID))
+}
+
+fn get_created_participant_contracts_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(CREATED_PARTICIPANT_CONTRACTS_MEMORY_ID))
+}
\ No newline at end of file
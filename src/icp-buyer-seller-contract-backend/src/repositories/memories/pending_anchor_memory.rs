@@ -0,0 +1,15 @@
+use ic_stable_structures::StableBTreeMap;
+use super::{Memory, MEMORY_MANAGER, PENDING_ANCHORS_MEMORY_ID};
+use crate::repositories::Uuid;
+
+
+/// Signed contracts whose current version still has to be anchored on the EVM chain
+pub type PendingAnchorMemory = StableBTreeMap<Uuid, (), Memory>;
+
+pub fn init_pending_anchors() -> PendingAnchorMemory {
+    StableBTreeMap::init(get_pending_anchors_memory())
+}
+
+fn get_pending_anchors_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(PENDING_ANCHORS_MEMORY_ID))
+}
\ No newline at end of file
@@ -0,0 +1,15 @@
+use candid::Principal;
+use ic_stable_structures::StableBTreeMap;
+use super::{Memory, MEMORY_MANAGER, PROFILES_MEMORY_ID};
+use crate::repositories::Profile;
+
+
+pub type ProfileMemory = StableBTreeMap<Principal, Profile, Memory>;
+
+pub fn init_profiles() -> ProfileMemory {
+    StableBTreeMap::init(get_profiles_memory())
+}
+
+fn get_profiles_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(PROFILES_MEMORY_ID))
+}
\ No newline at end of file
@@ -0,0 +1,14 @@
+use ic_stable_structures::StableBTreeMap;
+use super::{Memory, MEMORY_MANAGER, SCHEMAS_MEMORY_ID};
+use crate::repositories::ContractSchema;
+
+
+pub type SchemaMemory = StableBTreeMap<u32, ContractSchema, Memory>;
+
+pub fn init_schemas() -> SchemaMemory {
+    StableBTreeMap::init(get_schemas_memory())
+}
+
+fn get_schemas_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(SCHEMAS_MEMORY_ID))
+}
\ No newline at end of file
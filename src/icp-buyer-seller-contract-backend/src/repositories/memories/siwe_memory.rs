@@ -0,0 +1,25 @@
+use candid::Principal;
+use ic_stable_structures::StableBTreeMap;
+use super::{Memory, MEMORY_MANAGER, SIWE_ADDRESS_PRINCIPALS_MEMORY_ID, SIWE_PRINCIPAL_ADDRESSES_MEMORY_ID};
+
+
+/// Principal an Ethereum address logged in as with SIWE
+pub type AddressPrincipalMemory = StableBTreeMap<[u8; 20], Principal, Memory>;
+/// Ethereum address behind a principal obtained with SIWE
+pub type PrincipalAddressMemory = StableBTreeMap<Principal, [u8; 20], Memory>;
+
+pub fn init_address_principals() -> AddressPrincipalMemory {
+    StableBTreeMap::init(get_address_principals_memory())
+}
+
+pub fn init_principal_addresses() -> PrincipalAddressMemory {
+    StableBTreeMap::init(get_principal_addresses_memory())
+}
+
+fn get_address_principals_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(SIWE_ADDRESS_PRINCIPALS_MEMORY_ID))
+}
+
+fn get_principal_addresses_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(SIWE_PRINCIPAL_ADDRESSES_MEMORY_ID))
+}
\ No newline at end of file
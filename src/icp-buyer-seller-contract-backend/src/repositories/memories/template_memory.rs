@@ -0,0 +1,24 @@
+use ic_stable_structures::StableBTreeMap;
+use super::{Memory, MEMORY_MANAGER, TEMPLATES_MEMORY_ID, TEMPLATE_LATEST_VERSIONS_MEMORY_ID};
+use crate::repositories::{ContractTemplate, Uuid};
+
+
+pub type TemplateMemory = StableBTreeMap<(Uuid, u32), ContractTemplate, Memory>;
+/// Latest version of every template
+pub type TemplateLatestVersionMemory = StableBTreeMap<Uuid, u32, Memory>;
+
+pub fn init_templates() -> TemplateMemory {
+    StableBTreeMap::init(get_templates_memory())
+}
+
+pub fn init_template_latest_versions() -> TemplateLatestVersionMemory {
+    StableBTreeMap::init(get_template_latest_versions_memory())
+}
+
+fn get_templates_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(TEMPLATES_MEMORY_ID))
+}
+
+fn get_template_latest_versions_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(TEMPLATE_LATEST_VERSIONS_MEMORY_ID))
+}
\ No newline at end of file
@@ -0,0 +1,15 @@
+use candid::Principal;
+use ic_stable_structures::StableBTreeMap;
+use super::{Memory, MEMORY_MANAGER, VERIFICATIONS_MEMORY_ID};
+use crate::repositories::Verification;
+
+
+pub type VerificationMemory = StableBTreeMap<Principal, Verification, Memory>;
+
+pub fn init_verifications() -> VerificationMemory {
+    StableBTreeMap::init(get_verifications_memory())
+}
+
+fn get_verifications_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(VERIFICATIONS_MEMORY_ID))
+}
\ No newline at end of file
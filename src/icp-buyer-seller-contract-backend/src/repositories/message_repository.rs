@@ -0,0 +1,58 @@
+use std::cell::RefCell;
+
+use candid::Principal;
+use crate::repositories::{ContractMessage, Uuid};
+use super::{init_messages, MessageMemory};
+
+
+pub trait MessageRepository {
+    fn add_message(&self, contract_id: Uuid, author: Principal, text: String, attachment_id: Option<Uuid>) -> u64;
+    fn list_messages(&self, contract_id: Uuid, start: u64, limit: usize) -> Vec<ContractMessage>;
+}
+
+pub struct MessageRepositoryImpl;
+
+impl MessageRepository for MessageRepositoryImpl {
+    /// Append a message to the thread of a contract and return its index.
+    /// The last index is found by seeking just below the end of the contract's key range.
+    fn add_message(&self, contract_id: Uuid, author: Principal, text: String, attachment_id: Option<Uuid>) -> u64 {
+        STATE.with(|messages| {
+            let mut messages = messages.borrow_mut();
+            let index = messages
+                .iter_upper_bound(&(contract_id, u64::MAX))
+                .next()
+                .filter(|((thread, _), _)| *thread == contract_id)
+                .map_or(0, |((_, index), _)| index + 1);
+
+            messages.insert((contract_id, index), ContractMessage::new(index, author, text, attachment_id));
+            index
+        })
+    }
+
+    fn list_messages(&self, contract_id: Uuid, start: u64, limit: usize) -> Vec<ContractMessage> {
+        STATE.with(|messages| {
+            messages
+                .borrow()
+                .range((contract_id, start)..=(contract_id, u64::MAX))
+                .take(limit)
+                .map(|(_, message)| message)
+                .collect()
+        })
+    }
+}
+
+impl MessageRepositoryImpl {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for MessageRepositoryImpl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+thread_local! {
+    static STATE: RefCell<MessageMemory> = RefCell::new(init_messages());
+}
\ No newline at end of file
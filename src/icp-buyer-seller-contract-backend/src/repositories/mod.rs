@@ -1,9 +1,31 @@
 mod types;
 mod memories;
+mod attachment_repository;
+mod audit_repository;
+mod block_repository;
+mod config_repository;
 mod contract_repository;
+mod denylist_repository;
+mod message_repository;
+mod profile_repository;
+mod schema_repository;
+mod siwe_repository;
+mod template_repository;
 mod user_repository;
+mod verification_repository;
 
 use memories::*;
 pub use types::*;
+pub use attachment_repository::*;
+pub use audit_repository::*;
+pub use block_repository::*;
+pub use config_repository::*;
 pub use contract_repository::*;
-pub use user_repository::*;
\ No newline at end of file
+pub use denylist_repository::*;
+pub use message_repository::*;
+pub use profile_repository::*;
+pub use schema_repository::*;
+pub use siwe_repository::*;
+pub use template_repository::*;
+pub use user_repository::*;
+pub use verification_repository::*;
\ No newline at end of file
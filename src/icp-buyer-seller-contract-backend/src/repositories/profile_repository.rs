@@ -0,0 +1,41 @@
+use std::cell::RefCell;
+
+use candid::Principal;
+use crate::repositories::Profile;
+use super::{init_profiles, ProfileMemory};
+
+
+pub trait ProfileRepository {
+    fn get_profile(&self, principal: &Principal) -> Option<Profile>;
+    fn save_profile(&self, principal: Principal, profile: Profile);
+}
+
+pub struct ProfileRepositoryImpl;
+
+impl ProfileRepository for ProfileRepositoryImpl {
+    fn get_profile(&self, principal: &Principal) -> Option<Profile> {
+        STATE.with_borrow(|profiles| profiles.get(principal))
+    }
+
+    fn save_profile(&self, principal: Principal, profile: Profile) {
+        STATE.with_borrow_mut(|profiles| {
+            profiles.insert(principal, profile);
+        });
+    }
+}
+
+impl ProfileRepositoryImpl {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for ProfileRepositoryImpl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+thread_local! {
+    static STATE: RefCell<ProfileMemory> = RefCell::new(init_profiles());
+}
\ No newline at end of file
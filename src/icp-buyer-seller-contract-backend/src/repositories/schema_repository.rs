@@ -0,0 +1,55 @@
+use std::cell::RefCell;
+
+use candid::Principal;
+use crate::repositories::ContractSchema;
+use super::{init_schemas, SchemaMemory};
+
+
+pub trait SchemaRepository {
+    fn add_schema(&self, schema_json: String, registered_by: Principal) -> u32;
+    fn get_schema(&self, version: u32) -> Option<ContractSchema>;
+    fn get_latest_schema(&self) -> Option<ContractSchema>;
+    fn list_schemas(&self) -> Vec<ContractSchema>;
+}
+
+pub struct SchemaRepositoryImpl;
+
+impl SchemaRepository for SchemaRepositoryImpl {
+    /// Store a new schema version, which becomes the active one
+    fn add_schema(&self, schema_json: String, registered_by: Principal) -> u32 {
+        STATE.with(|schemas| {
+            let mut schemas = schemas.borrow_mut();
+            let version = schemas.last_key_value().map_or(1, |(version, _)| version + 1);
+            schemas.insert(version, ContractSchema::new(version, schema_json, registered_by));
+            version
+        })
+    }
+
+    fn get_schema(&self, version: u32) -> Option<ContractSchema> {
+        STATE.with(|schemas| schemas.borrow().get(&version))
+    }
+
+    fn get_latest_schema(&self) -> Option<ContractSchema> {
+        STATE.with(|schemas| schemas.borrow().last_key_value().map(|(_, schema)| schema))
+    }
+
+    fn list_schemas(&self) -> Vec<ContractSchema> {
+        STATE.with_borrow(|schemas| schemas.iter().map(|(_, schema)| schema).collect())
+    }
+}
+
+impl SchemaRepositoryImpl {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for SchemaRepositoryImpl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+thread_local! {
+    static STATE: RefCell<SchemaMemory> = RefCell::new(init_schemas());
+}
\ No newline at end of file
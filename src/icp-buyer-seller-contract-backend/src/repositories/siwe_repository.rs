@@ -0,0 +1,129 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, VecDeque};
+
+use alloy::primitives::Address;
+use candid::Principal;
+use crate::repositories::SiweMessage;
+use super::{init_address_principals, init_principal_addresses, AddressPrincipalMemory, PrincipalAddressMemory};
+
+
+pub trait SiweRepository {
+    fn add_message(&self, message: SiweMessage);
+    fn get_message(&self, nonce: &str) -> Option<SiweMessage>;
+    fn remove_message(&self, nonce: &str);
+    fn remove_expired_messages(&self, now: u64);
+    fn remove_oldest_message(&self);
+    fn list_message_nonces(&self, address: &str) -> Vec<String>;
+    fn count_messages(&self) -> usize;
+    fn link_address(&self, address: Address, principal: Principal);
+    fn get_principal_by_address(&self, address: Address) -> Option<Principal>;
+    fn get_address_by_principal(&self, principal: &Principal) -> Option<Address>;
+}
+
+pub struct SiweRepositoryImpl;
+
+impl SiweRepository for SiweRepositoryImpl {
+    /// Store a prepared login message by its nonce
+    fn add_message(&self, message: SiweMessage) {
+        EXPIRATIONS.with_borrow_mut(|expirations| {
+            expirations.push_back((message.expiration_time, message.nonce.clone()));
+        });
+        ADDRESS_NONCES.with_borrow_mut(|nonces| {
+            nonces.entry(message.address.clone()).or_default().push(message.nonce.clone());
+        });
+        MESSAGES.with_borrow_mut(|messages| {
+            messages.insert(message.nonce.clone(), message);
+        });
+    }
+
+    fn get_message(&self, nonce: &str) -> Option<SiweMessage> {
+        MESSAGES.with_borrow(|messages| messages.get(nonce).cloned())
+    }
+
+    /// Remove a prepared login message once it was used or replaced
+    fn remove_message(&self, nonce: &str) {
+        let Some(message) = MESSAGES.with_borrow_mut(|messages| messages.remove(nonce)) else {
+            return;
+        };
+        ADDRESS_NONCES.with_borrow_mut(|nonces| {
+            if let Some(address_nonces) = nonces.get_mut(&message.address) {
+                address_nonces.retain(|address_nonce| address_nonce != nonce);
+                if address_nonces.is_empty() {
+                    nonces.remove(&message.address);
+                }
+            }
+        });
+    }
+
+    /// Drop the messages expired at now. Messages all live for SIWE_MESSAGE_TTL,
+    /// so they expire in the order they were added and only the expired ones are visited.
+    fn remove_expired_messages(&self, now: u64) {
+        loop {
+            let expired = EXPIRATIONS.with_borrow_mut(|expirations| match expirations.front() {
+                Some((expiration_time, _)) if *expiration_time < now => expirations.pop_front(),
+                _ => None,
+            });
+            let Some((_, nonce)) = expired else {
+                break;
+            };
+            self.remove_message(&nonce);
+        }
+    }
+
+    /// Drop the message that expires first, skipping entries of messages already used
+    fn remove_oldest_message(&self) {
+        while let Some((_, nonce)) = EXPIRATIONS.with_borrow_mut(|expirations| expirations.pop_front()) {
+            if self.get_message(&nonce).is_some() {
+                self.remove_message(&nonce);
+                return;
+            }
+        }
+    }
+
+    /// Nonces of the pending messages of an address, oldest first
+    fn list_message_nonces(&self, address: &str) -> Vec<String> {
+        ADDRESS_NONCES.with_borrow(|nonces| nonces.get(address).cloned().unwrap_or_default())
+    }
+
+    fn count_messages(&self) -> usize {
+        MESSAGES.with_borrow(|messages| messages.len())
+    }
+
+    fn link_address(&self, address: Address, principal: Principal) {
+        ADDRESS_PRINCIPALS.with_borrow_mut(|principals| {
+            principals.insert(address.into_array(), principal);
+        });
+        PRINCIPAL_ADDRESSES.with_borrow_mut(|addresses| {
+            addresses.insert(principal, address.into_array());
+        });
+    }
+
+    fn get_principal_by_address(&self, address: Address) -> Option<Principal> {
+        ADDRESS_PRINCIPALS.with_borrow(|principals| principals.get(&address.into_array()))
+    }
+
+    fn get_address_by_principal(&self, principal: &Principal) -> Option<Address> {
+        PRINCIPAL_ADDRESSES.with_borrow(|addresses| addresses.get(principal).map(Address::from))
+    }
+}
+
+impl SiweRepositoryImpl {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for SiweRepositoryImpl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+thread_local! {
+    // prepared messages are short lived, a login started before an upgrade has to be prepared again
+    static MESSAGES: RefCell<BTreeMap<String, SiweMessage>> = const { RefCell::new(BTreeMap::new()) };
+    static EXPIRATIONS: RefCell<VecDeque<(u64, String)>> = const { RefCell::new(VecDeque::new()) };
+    static ADDRESS_NONCES: RefCell<BTreeMap<String, Vec<String>>> = const { RefCell::new(BTreeMap::new()) };
+    static ADDRESS_PRINCIPALS: RefCell<AddressPrincipalMemory> = RefCell::new(init_address_principals());
+    static PRINCIPAL_ADDRESSES: RefCell<PrincipalAddressMemory> = RefCell::new(init_principal_addresses());
+}
\ No newline at end of file
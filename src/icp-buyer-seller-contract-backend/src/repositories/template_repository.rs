@@ -0,0 +1,61 @@
+use std::cell::RefCell;
+
+use crate::repositories::{ContractTemplate, Uuid};
+use super::{init_template_latest_versions, init_templates, TemplateLatestVersionMemory, TemplateMemory};
+
+
+pub trait TemplateRepository {
+    fn add_template(&self, template: ContractTemplate);
+    fn get_template(&self, template_id: Uuid, version: u32) -> Option<ContractTemplate>;
+    fn get_latest_template(&self, template_id: Uuid) -> Option<ContractTemplate>;
+    fn list_latest_templates(&self) -> Vec<ContractTemplate>;
+}
+
+pub struct TemplateRepositoryImpl;
+
+impl TemplateRepository for TemplateRepositoryImpl {
+    fn add_template(&self, template: ContractTemplate) {
+        LATEST_VERSIONS.with_borrow_mut(|versions| versions.insert(template.template_id, template.version));
+        STATE.with(|templates| {
+            templates
+                .borrow_mut()
+                .insert((template.template_id, template.version), template);
+        });
+    }
+
+    fn get_template(&self, template_id: Uuid, version: u32) -> Option<ContractTemplate> {
+        STATE.with(|templates| templates.borrow().get(&(template_id, version)))
+    }
+
+    fn get_latest_template(&self, template_id: Uuid) -> Option<ContractTemplate> {
+        let version = LATEST_VERSIONS.with_borrow(|versions| versions.get(&template_id))?;
+        self.get_template(template_id, version)
+    }
+
+    /// List the latest version of every template
+    fn list_latest_templates(&self) -> Vec<ContractTemplate> {
+        LATEST_VERSIONS.with_borrow(|versions| {
+            versions
+                .iter()
+                .filter_map(|(template_id, version)| self.get_template(template_id, version))
+                .collect()
+        })
+    }
+}
+
+impl TemplateRepositoryImpl {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for TemplateRepositoryImpl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+thread_local! {
+    static STATE: RefCell<TemplateMemory> = RefCell::new(init_templates());
+    static LATEST_VERSIONS: RefCell<TemplateLatestVersionMemory> = RefCell::new(init_template_latest_versions());
+}
\ No newline at end of file
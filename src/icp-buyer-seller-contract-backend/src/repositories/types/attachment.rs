@@ -0,0 +1,121 @@
+use std::borrow::Cow;
+use candid::{CandidType, Decode, Deserialize, Encode, Principal};
+use ic_stable_structures::{storable::Bound, Storable};
+use super::{result::ApiError, validate_content_hash, Uuid};
+
+/// Maximum size of a single uploaded chunk, below the 2 MiB ingress message limit
+pub const MAX_ATTACHMENT_CHUNK_SIZE: u64 = 1024 * 1024;
+pub const MAX_ATTACHMENT_SIZE: u64 = 20 * 1024 * 1024;
+pub const MAX_ATTACHMENTS_PER_CONTRACT: usize = 50;
+pub const MAX_ATTACHMENT_FILE_NAME_SIZE: usize = 255;
+/// Time after which an upload that has not been finalized is discarded, in nanoseconds
+pub const PENDING_ATTACHMENT_TTL: u64 = 24 * 60 * 60 * 1_000_000_000;
+
+/// MIME types accepted for trade documents
+pub const ALLOWED_ATTACHMENT_MIME_TYPES: [&str; 7] = [
+    "application/pdf",
+    "application/json",
+    "application/xml",
+    "text/plain",
+    "text/csv",
+    "image/png",
+    "image/jpeg",
+];
+
+#[derive(Clone, Copy, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub enum AttachmentStatus {
+    /// chunks are still being uploaded
+    Pending,
+    /// all chunks are uploaded and the sha256 has been verified
+    Complete,
+}
+
+/// Metadata of a document attached to a contract, e.g. an invoice or bill of lading.
+/// The file content is stored separately in chunks of at most MAX_ATTACHMENT_CHUNK_SIZE bytes.
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct Attachment {
+    pub attachment_id: Uuid,
+    pub contract_id: Uuid,
+    pub file_name: String,
+    pub mime_type: String,
+    pub size: u64,
+    /// hex encoded sha256 of the file, declared by the uploader and verified on finalization
+    pub sha256: String,
+    pub chunk_count: u32,
+    pub status: AttachmentStatus,
+    pub uploaded_by: Principal,
+    pub created_at: u64,
+}
+
+impl Storable for Attachment {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl Attachment {
+    /// Check the declared metadata of a new attachment
+    pub fn new(contract_id: Uuid, file_name: String, mime_type: String, size: u64, sha256: String, uploaded_by: Principal) -> Result<Self, ApiError> {
+        if file_name.is_empty() || file_name.len() > MAX_ATTACHMENT_FILE_NAME_SIZE {
+            return Err(ApiError::invalid_argument(&format!(
+                "File name must be between 1 and {} bytes",
+                MAX_ATTACHMENT_FILE_NAME_SIZE
+            )));
+        }
+
+        let mime_type = mime_type.to_ascii_lowercase();
+        if !ALLOWED_ATTACHMENT_MIME_TYPES.contains(&mime_type.as_str()) {
+            return Err(ApiError::invalid_argument(&format!("MIME type {} is not supported", mime_type)));
+        }
+
+        if size == 0 || size > MAX_ATTACHMENT_SIZE {
+            return Err(ApiError::invalid_argument(&format!(
+                "Attachment size must be between 1 and {} bytes",
+                MAX_ATTACHMENT_SIZE
+            )));
+        }
+
+        validate_content_hash(&sha256)?;
+
+        Ok(Self {
+            attachment_id: Uuid::new(),
+            contract_id,
+            file_name,
+            mime_type,
+            size,
+            sha256: sha256.to_lowercase(),
+            chunk_count: size.div_ceil(MAX_ATTACHMENT_CHUNK_SIZE) as u32,
+            status: AttachmentStatus::Pending,
+            uploaded_by,
+            created_at: ic_cdk::api::time(),
+        })
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.status == AttachmentStatus::Complete
+    }
+
+    /// Whether the upload was started more than PENDING_ATTACHMENT_TTL ago and never finalized
+    pub fn is_expired(&self, now: u64) -> bool {
+        !self.is_complete() && now >= self.created_at.saturating_add(PENDING_ATTACHMENT_TTL)
+    }
+
+    /// Expected size of the chunk at index, all chunks but the last one are full
+    pub fn chunk_size(&self, index: u32) -> Option<u64> {
+        if index >= self.chunk_count {
+            return None;
+        }
+
+        if index + 1 < self.chunk_count {
+            Some(MAX_ATTACHMENT_CHUNK_SIZE)
+        } else {
+            Some(self.size - MAX_ATTACHMENT_CHUNK_SIZE * index as u64)
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,54 @@
+use std::borrow::Cow;
+use candid::{CandidType, Decode, Deserialize, Encode};
+use ic_stable_structures::{storable::Bound, Storable};
+use super::ContractStatus;
+
+/// State of a contract signed by the canister's attestation key as EIP-712 typed data:
+/// domain { name: "TradeOnChain", version: "1", chainId },
+/// ContractAttestation(bytes16 contractId,bytes32 contentHash,string status,uint32 version,uint256 price,string currency,uint64 timestamp).
+/// A missing price is signed as 0 and a missing currency as "".
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct ContractAttestation {
+    pub contract_id: String,
+    pub content_hash: String,
+    pub status: ContractStatus,
+    pub version: u32,
+    pub price: Option<u64>,
+    pub currency: Option<String>,
+    /// seconds since the unix epoch, as in the signed typed data
+    pub timestamp: u64,
+    pub chain_id: u64,
+    /// EIP-712 signing hash, 0x hex
+    pub digest: String,
+    /// r ++ s ++ v, 0x hex
+    pub signature: String,
+    /// Ethereum address of the signing key
+    pub signer: String,
+}
+
+impl ContractAttestation {
+    /// Whether this attestation still covers the given state of the contract
+    pub fn is_current(&self, version: u32, status: ContractStatus, chain_id: u64) -> bool {
+        self.version == version && self.status == status && self.chain_id == chain_id
+    }
+}
+
+impl Storable for ContractAttestation {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// Threshold ECDSA key attestations are signed with, derived from the canister key with ATTESTATION_DERIVATION_PATH
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct AttestationPublicKey {
+    /// SEC1 compressed secp256k1 public key, 0x hex
+    pub public_key: String,
+    pub address: String,
+}
\ No newline at end of file
@@ -0,0 +1,111 @@
+use std::borrow::Cow;
+use candid::{CandidType, Decode, Deserialize, Encode, Principal};
+use ic_stable_structures::{storable::Bound, Storable};
+use super::Uuid;
+
+pub const DEFAULT_AUDIT_PAGE_SIZE: u32 = 50;
+pub const MAX_AUDIT_PAGE_SIZE: u32 = 200;
+
+#[derive(Clone, Copy, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub enum AuditAction {
+    ContractCreated,
+    ContractAmended,
+    ContractSigned,
+    SignatureRevoked,
+    PaymentStatusUpdated,
+    PermissionAdded,
+    PermissionRemoved,
+    PermissionUpdated,
+    ConfigUpdated,
+    SchemaRegistered,
+    TemplateRegistered,
+    AttachmentCreated,
+    AttachmentFinalized,
+    AttachmentRemoved,
+    ContractAnchored,
+    AnchoringFailed,
+    EvmSignatoryLinked,
+    VerificationUpdated,
+    VerificationRemoved,
+    DenylistUpdated,
+    DenylistBlocked,
+    AdminRestored,
+}
+
+/// An entry of the append-only audit log.
+/// before and after are short human readable summaries of the state that was changed.
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct AuditEntry {
+    /// position in the audit log, assigned when the entry is appended
+    pub index: u64,
+    pub timestamp: u64,
+    pub actor: Principal,
+    pub action: AuditAction,
+    pub contract_id: Option<Uuid>,
+    /// principal affected by the change, e.g. the user whose permission was updated
+    pub subject: Option<Principal>,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+impl Storable for AuditEntry {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl AuditEntry {
+    pub fn new(actor: Principal, action: AuditAction) -> Self {
+        Self {
+            index: 0,
+            timestamp: ic_cdk::api::time(),
+            actor,
+            action,
+            contract_id: None,
+            subject: None,
+            before: None,
+            after: None,
+        }
+    }
+
+    pub fn with_contract(mut self, contract_id: Uuid) -> Self {
+        self.contract_id = Some(contract_id);
+        self
+    }
+
+    pub fn with_subject(mut self, subject: Principal) -> Self {
+        self.subject = Some(subject);
+        self
+    }
+
+    pub fn with_change(mut self, before: Option<String>, after: Option<String>) -> Self {
+        self.before = before;
+        self.after = after;
+        self
+    }
+
+    /// Principals the entry is indexed by: the actor and, if different, the subject
+    pub fn principals(&self) -> Vec<Principal> {
+        let mut principals = vec![self.actor];
+        if let Some(subject) = self.subject {
+            if subject != self.actor {
+                principals.push(subject);
+            }
+        }
+        principals
+    }
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct AuditPage {
+    /// entries in the order they were recorded
+    pub entries: Vec<AuditEntry>,
+    /// index to pass as start for the next page, none if this is the last one
+    pub next_index: Option<u64>,
+}
\ No newline at end of file
@@ -0,0 +1,48 @@
+use std::borrow::Cow;
+use candid::{CandidType, Decode, Deserialize, Encode};
+use ic_stable_structures::{storable::Bound, Storable};
+use serde_bytes::ByteBuf;
+use super::{Contract, Hash};
+
+/// A contract with a certificate and a witness for its hash.
+/// The witness reveals contracts/<contract id>, which must equal the sha256 of encoded_contract.
+/// Candid encodings are not canonical, so clients check the hash against encoded_contract
+/// and decode it rather than re-encoding contract.
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct CertifiedContract {
+    pub contract: Contract,
+    /// candid encoding of contract, as hashed in the certified tree
+    pub encoded_contract: ByteBuf,
+    pub certificate: ByteBuf,
+    /// CBOR encoded hash tree
+    pub witness: ByteBuf,
+}
+
+/// Hash and signature status of a contract as certified in the tree, see Contract::certified_hash.
+/// They are kept in stable memory so the tree can be rebuilt after an upgrade without decoding every contract.
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct CertifiedHash {
+    pub hash: Hash,
+    pub is_signed: bool,
+}
+
+impl Storable for CertifiedHash {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// The signature status of a contract with a certificate and a witness for signed/<contract id>
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct CertifiedSignatureStatus {
+    pub is_signed: bool,
+    pub certificate: ByteBuf,
+    /// CBOR encoded hash tree
+    pub witness: ByteBuf,
+}
\ No newline at end of file
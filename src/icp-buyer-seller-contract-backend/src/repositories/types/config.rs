@@ -0,0 +1,55 @@
+use std::borrow::Cow;
+use candid::{CandidType, Decode, Deserialize, Encode};
+use ic_stable_structures::{storable::Bound, Storable};
+use super::VerificationRequirement;
+
+/// Canister wide settings managed by admins
+#[derive(Clone, Debug, Default, CandidType, Deserialize)]
+pub struct Config {
+    pub public_summary: PublicSummaryConfig,
+    /// anchoring of signed contracts on the EVM chain, disabled if not set
+    pub anchor: Option<AnchorConfig>,
+    /// Sign-In with Ethereum, disabled if not set
+    pub siwe: Option<SiweConfig>,
+    /// verification levels parties need by contract price, none required if not set
+    pub verification_requirements: Option<Vec<VerificationRequirement>>,
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct AnchorConfig {
+    /// address of the registry contract the Merkle roots are written to
+    pub registry_address: String,
+}
+
+/// Fields of the EIP-4361 messages shown to users logging in with their Ethereum wallet
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct SiweConfig {
+    /// domain of the frontend requesting the login, e.g. app.tradeonchain.com
+    pub domain: String,
+    pub uri: String,
+    pub statement: Option<String>,
+    pub chain_id: u64,
+    /// lifetime of the delegation issued on login in nanoseconds, 8 hours if not set
+    pub session_expires_in: Option<u64>,
+}
+
+/// Controls the public summary of a contract, readable by anyone who knows its id.
+/// Status, version and creation time are always part of an enabled summary.
+#[derive(Clone, Debug, Default, CandidType, Deserialize)]
+pub struct PublicSummaryConfig {
+    pub enabled: bool,
+    pub include_signatories: bool,
+    pub include_content_hash: bool,
+}
+
+impl Storable for Config {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
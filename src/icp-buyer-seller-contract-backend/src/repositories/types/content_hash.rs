@@ -0,0 +1,220 @@
+use std::fmt::Write;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use super::result::ApiError;
+
+/// Compute the hex encoded sha256 of the canonical form of a contract json.
+///
+/// The canonical form is the JSON Canonicalization Scheme (RFC 8785), so frontends can recompute
+/// the same hash with any JCS implementation regardless of how the json was formatted.
+pub fn content_hash(contract_json: &str) -> Result<String, ApiError> {
+    let canonical = canonicalize_json(contract_json)?;
+
+    Ok(hex::encode(Sha256::digest(canonical.as_bytes())))
+}
+
+/// Check that a content hash provided by a client is a hex encoded sha256
+pub fn validate_content_hash(content_hash: &str) -> Result<(), ApiError> {
+    if content_hash.len() != 64 || !content_hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(ApiError::invalid_argument("Content hash must be a hex encoded sha256"));
+    }
+
+    Ok(())
+}
+
+/// Serialize a json document as defined by RFC 8785: no whitespace, object keys sorted by their
+/// UTF-16 code units, strings escaped like ECMAScript's JSON.stringify and numbers serialized
+/// as IEEE 754 doubles with ECMAScript's Number.prototype.toString.
+pub fn canonicalize_json(contract_json: &str) -> Result<String, ApiError> {
+    let value: Value = serde_json::from_str(contract_json).map_err(|e| {
+        ApiError::invalid_argument(&format!("Contract json is not valid json: {}", e))
+    })?;
+
+    let mut canonical = String::with_capacity(contract_json.len());
+    write_canonical(&value, &mut canonical);
+
+    Ok(canonical)
+}
+
+fn write_canonical(value: &Value, out: &mut String) {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.encode_utf16().cmp(b.0.encode_utf16()));
+
+            out.push('{');
+            for (i, (key, value)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_string(key, out);
+                out.push(':');
+                write_canonical(value, out);
+            }
+            out.push('}');
+        }
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        Value::String(string) => write_string(string, out),
+        // json numbers are always finite
+        Value::Number(number) => write_number(number.as_f64().unwrap_or_default(), out),
+        Value::Bool(_) | Value::Null => out.push_str(&value.to_string()),
+    }
+}
+
+fn write_string(string: &str, out: &mut String) {
+    out.push('"');
+    for c in string.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\u{08}' => out.push_str("\\b"),
+            '\u{0c}' => out.push_str("\\f"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c < ' ' => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// ECMAScript Number.prototype.toString of a finite double
+fn write_number(value: f64, out: &mut String) {
+    // also covers -0
+    if value == 0.0 {
+        out.push('0');
+        return;
+    }
+    if value < 0.0 {
+        out.push('-');
+    }
+
+    // shortest digits that round trip, e.g. 1.2345e-7
+    let scientific = format!("{:e}", value.abs());
+    let (mantissa, exponent) = scientific.split_once('e').unwrap_or((&scientific, "0"));
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    let k = digits.len() as i32;
+    // value = 0.digits * 10^n
+    let n = exponent.parse::<i32>().unwrap_or_default() + 1;
+
+    if k <= n && n <= 21 {
+        out.push_str(&digits);
+        out.push_str(&"0".repeat((n - k) as usize));
+    } else if 0 < n && n <= 21 {
+        out.push_str(&digits[..n as usize]);
+        out.push('.');
+        out.push_str(&digits[n as usize..]);
+    } else if -6 < n && n <= 0 {
+        out.push_str("0.");
+        out.push_str(&"0".repeat(-n as usize));
+        out.push_str(&digits);
+    } else {
+        out.push_str(&digits[..1]);
+        if k > 1 {
+            out.push('.');
+            out.push_str(&digits[1..]);
+        }
+        let _ = write!(out, "e{}{}", if n > 1 { '+' } else { '-' }, (n - 1).abs());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn number(bits: u64) -> String {
+        let mut out = String::new();
+        write_number(f64::from_bits(bits), &mut out);
+        out
+    }
+
+    #[test]
+    fn serializes_numbers_like_rfc_8785() {
+        let cases = [
+            (0x0000000000000000, "0"),
+            (0x8000000000000000, "0"),
+            (0x0000000000000001, "5e-324"),
+            (0x8000000000000001, "-5e-324"),
+            (0x7fefffffffffffff, "1.7976931348623157e+308"),
+            (0xffefffffffffffff, "-1.7976931348623157e+308"),
+            (0x4340000000000000, "9007199254740992"),
+            (0xc340000000000000, "-9007199254740992"),
+            (0x4430000000000000, "295147905179352830000"),
+            (0x44b52d02c7e14af5, "9.999999999999997e+22"),
+            (0x44b52d02c7e14af6, "1e+23"),
+            (0x44b52d02c7e14af7, "1.0000000000000001e+23"),
+            (0x444b1ae4d6e2ef4e, "999999999999999700000"),
+            (0x444b1ae4d6e2ef4f, "999999999999999900000"),
+            (0x444b1ae4d6e2ef50, "1e+21"),
+            (0x3eb0c6f7a0b5ed8c, "9.999999999999997e-7"),
+            (0x3eb0c6f7a0b5ed8d, "0.000001"),
+            (0x41b3de4355555553, "333333333.3333332"),
+            (0x41b3de4355555554, "333333333.33333325"),
+            (0x41b3de4355555555, "333333333.3333333"),
+            (0x41b3de4355555556, "333333333.3333334"),
+            (0x41b3de4355555557, "333333333.33333343"),
+            (0xbecbf647612f3696, "-0.0000033333333333333333"),
+            (0x43143ff3c1cb0959, "1424953923781206.2"),
+        ];
+
+        for (bits, expected) in cases {
+            assert_eq!(number(bits), expected, "{:016x}", bits);
+        }
+    }
+
+    #[test]
+    fn canonicalizes_rfc_8785_example() {
+        let input = r#"{
+            "numbers": [333333333.33333329, 1E30, 4.50, 2e-3, 0.000000000000000000000000001],
+            "string": "\u20ac$\u000F\u000aA'\u0042\u0022\u005c\\\"\/",
+            "literals": [null, true, false]
+        }"#;
+        let expected = r#"{"literals":[null,true,false],"numbers":[333333333.3333333,1e+30,4.5,0.002,1e-27],"string":"€$\u000f\nA'B\"\\\\\"/"}"#;
+
+        assert_eq!(canonicalize_json(input).unwrap(), expected);
+    }
+
+    #[test]
+    fn sorts_keys_by_utf16_code_units() {
+        let input = r#"{
+            "\u20ac": "Euro Sign",
+            "\r": "Carriage Return",
+            "\ufb33": "Hebrew Letter Dalet With Dagesh",
+            "1": "One",
+            "\ud83d\ude00": "Emoji: Grinning Face",
+            "\u0080": "Control",
+            "\u00f6": "Latin Small Letter O With Diaeresis"
+        }"#;
+        let expected = concat!(
+            "{\"\\r\":\"Carriage Return\",",
+            "\"1\":\"One\",",
+            "\"\u{80}\":\"Control\",",
+            "\"\u{f6}\":\"Latin Small Letter O With Diaeresis\",",
+            "\"\u{20ac}\":\"Euro Sign\",",
+            "\"\u{1f600}\":\"Emoji: Grinning Face\",",
+            "\"\u{fb33}\":\"Hebrew Letter Dalet With Dagesh\"}",
+        );
+
+        assert_eq!(canonicalize_json(input).unwrap(), expected);
+    }
+
+    #[test]
+    fn hash_ignores_formatting() {
+        let compact = r#"{"b":1,"a":[true,null]}"#;
+        let formatted = "{\n  \"a\": [ true, null ],\n  \"b\": 1.0\n}";
+
+        assert_eq!(content_hash(compact).unwrap(), content_hash(formatted).unwrap());
+    }
+}
\ No newline at end of file
@@ -4,13 +4,239 @@ use ic_stable_structures::{
     storable::{Blob, Bound},
     Storable,
 };
+use sha2::{Digest, Sha256};
+use super::{content_hash, result::ApiError, CertifiedHash, ContractTerms, Hash, TemplateReference};
 
-/// A struct representing the signatories of a contract.
-/// bool flag represents if they have signed the contract
+/// Upper bound for the size of a contract json, in bytes
+pub const MAX_CONTRACT_JSON_SIZE: usize = 64 * 1024;
+
+/// Proof of a signature on a specific version of the contract json.
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct ContractSignature {
+    /// IC time at which the signature was recorded
+    pub signed_at: u64,
+    /// version of the contract json that was signed
+    pub version: u32,
+    /// content hash of the contract json that was signed
+    pub content_hash: String,
+    /// origin of the delegation used by the caller, as reported by the frontend.
+    /// The IC does not expose the caller's delegation chain to canisters.
+    pub delegation_origin: Option<String>,
+    /// EIP-712 signature as 0x hex, if the signatory signed with its Ethereum wallet
+    pub evm_signature: Option<String>,
+}
+
+impl ContractSignature {
+    pub fn new(contract: &Contract, delegation_origin: Option<String>) -> Self {
+        Self {
+            signed_at: ic_cdk::api::time(),
+            version: contract.version,
+            content_hash: contract.content_hash.clone(),
+            delegation_origin,
+            evm_signature: None,
+        }
+    }
+
+    pub fn with_evm_signature(mut self, evm_signature: String) -> Self {
+        self.evm_signature = Some(evm_signature);
+        self
+    }
+}
+
+/// EIP-712 signature of a contract made with an Ethereum wallet, see `sign_contract`
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct EvmSignature {
+    pub address: String,
+    /// r ++ s ++ v, 0x hex
+    pub signature: String,
+}
+
+/// Role a signatory holds in a contract
+#[derive(Clone, Copy, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub enum SignatoryRole {
+    Buyer,
+    Seller,
+    Guarantor,
+    Carrier,
+    Broker,
+}
+
+impl Storable for SignatoryRole {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// Encryption details of a contract whose json is stored as ciphertext.
+///
+/// The json is encrypted with the vetKey derived from the creator principal and a random key id,
+/// see `derivation_input`. The key is only released to the signatories of the contract.
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct ContractEncryption {
+    pub key_id: Vec<u8>,
+    pub created_by: Principal,
+}
+
+impl ContractEncryption {
+    /// vetKD derivation input: the creator principal bytes followed by the key id.
+    /// Binding the creator prevents another caller from creating a contract with the same key id
+    /// to obtain the key.
+    pub fn derivation_input(&self) -> Vec<u8> {
+        let mut input = self.created_by.as_slice().to_vec();
+        input.extend_from_slice(&self.key_id);
+        input
+    }
+}
+
+/// Lifecycle status of a contract, derived from its signatures and payment
+#[derive(Clone, Copy, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub enum ContractStatus {
+    /// nobody has signed the current version yet
+    Draft,
+    /// some signatures are collected but the quorum is not met
+    PartiallySigned,
+    Signed,
+    Paid,
+}
+
+/// A party of a contract, with its signature if it has signed the current version.
+/// A signatory is identified by its principal or, if it signs with an Ethereum wallet, by its address.
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct Signatory {
+    pub principal: Option<Principal>,
+    /// checksummed Ethereum address
+    pub evm_address: Option<String>,
+    pub role: SignatoryRole,
+    pub signature: Option<ContractSignature>,
+}
+
+/// Identifies the signatory a signature belongs to
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SignatoryId {
+    Principal(Principal),
+    EvmAddress(String),
+}
+
+impl Signatory {
+    pub fn new(principal: Principal, role: SignatoryRole) -> Self {
+        Self {
+            principal: Some(principal),
+            evm_address: None,
+            role,
+            signature: None,
+        }
+    }
+
+    pub fn new_evm(evm_address: String, role: SignatoryRole) -> Self {
+        Self {
+            principal: None,
+            evm_address: Some(evm_address),
+            role,
+            signature: None,
+        }
+    }
+
+    pub fn is(&self, id: &SignatoryId) -> bool {
+        match id {
+            SignatoryId::Principal(principal) => self.principal.as_ref() == Some(principal),
+            SignatoryId::EvmAddress(address) => self.evm_address.as_ref() == Some(address),
+        }
+    }
+
+    pub fn has_signed(&self) -> bool {
+        self.signature.is_some()
+    }
+
+    pub fn has_signed_version(&self, version: u32) -> bool {
+        self.signature.as_ref().is_some_and(|signature| signature.version == version)
+    }
+}
+
+/// Rule deciding when a contract counts as signed
 #[derive(Clone, Debug, CandidType, Deserialize)]
-pub struct ContractSignatories {
-    pub buyer: (Principal, bool),
-    pub seller: (Principal, bool),
+pub enum SignatureQuorum {
+    /// every signatory has to sign
+    All,
+    /// at least this many signatories have to sign
+    Threshold(u32),
+    /// every signatory holding one of these roles has to sign
+    Roles(Vec<SignatoryRole>),
+}
+
+impl SignatureQuorum {
+    /// Check that the rule can be satisfied by the given signatories
+    pub fn validate(&self, signatories: &[Signatory]) -> Result<(), ApiError> {
+        match self {
+            SignatureQuorum::All => Ok(()),
+            SignatureQuorum::Threshold(threshold) => {
+                if *threshold == 0 || *threshold as usize > signatories.len() {
+                    return Err(ApiError::invalid_argument(&format!(
+                        "Signature threshold must be between 1 and {}",
+                        signatories.len()
+                    )));
+                }
+                Ok(())
+            }
+            SignatureQuorum::Roles(roles) => {
+                if roles.is_empty() {
+                    return Err(ApiError::invalid_argument("Signature quorum must require at least one role"));
+                }
+                if let Some(role) = roles.iter().find(|role| !signatories.iter().any(|s| &s.role == *role)) {
+                    return Err(ApiError::invalid_argument(&format!(
+                        "Signature quorum requires role {:?} but no signatory holds it",
+                        role
+                    )));
+                }
+                Ok(())
+            }
+        }
+    }
+
+    pub fn is_met(&self, signatories: &[Signatory]) -> bool {
+        match self {
+            SignatureQuorum::All => signatories.iter().all(Signatory::has_signed),
+            SignatureQuorum::Threshold(threshold) => {
+                signatories.iter().filter(|s| s.has_signed()).count() >= *threshold as usize
+            }
+            SignatureQuorum::Roles(roles) => signatories
+                .iter()
+                .filter(|s| roles.contains(&s.role))
+                .all(Signatory::has_signed),
+        }
+    }
+}
+
+/// Kind of change recorded in the history of a contract
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub enum ContractEventKind {
+    Created,
+    Amended { version: u32 },
+    Signed,
+    SignatureRevoked,
+}
+
+/// An entry in the history of a contract, timestamped with the IC time
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct ContractEvent {
+    pub kind: ContractEventKind,
+    pub actor: Principal,
+    pub timestamp: u64,
+}
+
+impl ContractEvent {
+    pub fn new(kind: ContractEventKind, actor: Principal) -> Self {
+        Self {
+            kind,
+            actor,
+            timestamp: ic_cdk::api::time(),
+        }
+    }
 }
 
 /// A struct representing a contract.
@@ -18,15 +244,54 @@ pub struct ContractSignatories {
 /// The contract json is a json string representation of the contract computed offchain
 #[derive(Clone, Debug, CandidType, Deserialize)]
 pub struct Contract {
-    pub signatories: ContractSignatories,
+    pub signatories: Vec<Signatory>,
+    pub quorum: SignatureQuorum,
     pub contract_json: String,
+    /// hex encoded sha256 of the canonicalized contract json, see `content_hash`
+    pub content_hash: String,
+    /// version of the current contract json, starting at 1 and bumped by every amendment
+    pub version: u32,
+    /// key terms extracted from the current contract json
+    pub terms: ContractTerms,
     pub created_at: u64,
-    //TODO: add for validation?
-    //pub amount : u64,
     pub issued_payment : bool,
+    /// signature and amendment events, oldest first
+    pub history: Vec<ContractEvent>,
+    /// set if contract_json holds ciphertext; content_hash is then provided by the creator
+    pub encryption: Option<ContractEncryption>,
+    /// last anchoring of the signed contract on the EVM chain
+    pub anchor: Option<ContractAnchor>,
+    /// set if the contract was created from a template
+    pub template: Option<TemplateReference>,
 }
 
-impl Storable for Contract {
+/// Record of a signed contract version anchored on the EVM registry contract.
+/// The leaf keccak256(contract id bytes ++ content hash) is part of a Merkle tree whose root
+/// was written to the registry in transaction tx_hash. Hashes are 0x prefixed hex strings.
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct ContractAnchor {
+    pub version: u32,
+    pub content_hash: String,
+    pub leaf: String,
+    pub merkle_root: String,
+    /// sibling hashes from the leaf up to the root, pairs are hashed in sorted order
+    pub merkle_proof: Vec<String>,
+    pub tx_hash: String,
+    pub anchored_at: u64,
+}
+
+/// A single version of the contract json.
+/// The first version is stored on creation, every amendment adds a new one.
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct ContractVersion {
+    pub version: u32,
+    pub contract_json: String,
+    pub content_hash: String,
+    pub proposed_by: Principal,
+    pub created_at: u64,
+}
+
+impl Storable for ContractVersion {
     fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
         Cow::Owned(Encode!(self).unwrap())
     }
@@ -38,24 +303,229 @@ impl Storable for Contract {
     const BOUND: Bound = Bound::Unbounded;
 }
 
+impl ContractVersion {
+    pub fn new(version: u32, contract_json: String, content_hash: String, proposed_by: Principal) -> Self {
+        Self {
+            version,
+            contract_json,
+            content_hash,
+            proposed_by,
+            created_at: ic_cdk::api::time(),
+        }
+    }
+}
+
+/// Two-party signatories of contracts stored before multi-party contracts,
+/// the flag tells whether the party has signed
+#[derive(CandidType, Deserialize)]
+struct LegacyContractSignatories {
+    buyer: (Principal, bool),
+    seller: (Principal, bool),
+}
+
+/// Contracts stored before versioning, signature details and extracted terms
+#[derive(CandidType, Deserialize)]
+struct LegacyContract {
+    signatories: LegacyContractSignatories,
+    contract_json: String,
+    created_at: u64,
+    issued_payment: bool,
+}
+
+impl From<LegacyContract> for Contract {
+    /// The legacy json becomes version 1. Signatures carry no time or origin,
+    /// they are recorded as made at creation time on that version.
+    fn from(legacy: LegacyContract) -> Self {
+        let content_hash = content_hash(&legacy.contract_json)
+            .unwrap_or_else(|_| hex::encode(Sha256::digest(legacy.contract_json.as_bytes())));
+        let terms = serde_json::from_str(&legacy.contract_json)
+            .ok()
+            .and_then(|json| ContractTerms::from_json(&json).ok())
+            .unwrap_or_default();
+
+        let signatory = |(principal, signed): (Principal, bool), role: SignatoryRole| Signatory {
+            signature: signed.then(|| ContractSignature {
+                signed_at: legacy.created_at,
+                version: 1,
+                content_hash: content_hash.clone(),
+                delegation_origin: None,
+                evm_signature: None,
+            }),
+            ..Signatory::new(principal, role)
+        };
+        let signatories = vec![
+            signatory(legacy.signatories.buyer, SignatoryRole::Buyer),
+            signatory(legacy.signatories.seller, SignatoryRole::Seller),
+        ];
+
+        Self {
+            signatories,
+            quorum: SignatureQuorum::All,
+            contract_json: legacy.contract_json,
+            content_hash,
+            version: 1,
+            terms,
+            created_at: legacy.created_at,
+            issued_payment: legacy.issued_payment,
+            history: vec![],
+            encryption: None,
+            anchor: None,
+            template: None,
+        }
+    }
+}
+
+impl Storable for Contract {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap_or_else(|_| {
+            Decode!(bytes.as_ref(), LegacyContract).unwrap().into()
+        })
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
 impl Contract {
-    pub fn new(contract_json: String, buyer: Principal, seller: Principal) -> Self {
+    pub fn new(
+        contract_json: String,
+        content_hash: String,
+        terms: ContractTerms,
+        signatories: Vec<Signatory>,
+        quorum: SignatureQuorum,
+        created_by: Principal,
+    ) -> Self {
         Self {
-            signatories: ContractSignatories {
-                buyer: (buyer, false),
-                seller: (seller, false),
-            },
+            signatories,
+            quorum,
             contract_json,
+            content_hash,
+            version: 1,
+            terms,
             created_at: ic_cdk::api::time(),
             issued_payment: false,
+            history: vec![ContractEvent::new(ContractEventKind::Created, created_by)],
+            encryption: None,
+            anchor: None,
+            template: None,
         }
     }
 
+    pub fn with_template(mut self, template: TemplateReference) -> Self {
+        self.template = Some(template);
+        self
+    }
+
+    pub fn with_encryption(mut self, encryption: ContractEncryption) -> Self {
+        self.encryption = Some(encryption);
+        self
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        self.encryption.is_some()
+    }
+
+    /// Whether the current version is signed but has not been anchored yet
+    pub fn needs_anchor(&self) -> bool {
+        self.is_signed() && self.anchor.as_ref().is_none_or(|anchor| anchor.version != self.version)
+    }
+
+    /// Replace the contract json with an amended version.
+    /// Existing signatures are invalidated, so every party has to sign again.
+    pub fn amend(&mut self, contract_json: String, content_hash: String, terms: ContractTerms, proposed_by: Principal) -> u32 {
+        self.contract_json = contract_json;
+        self.content_hash = content_hash;
+        self.terms = terms;
+        self.version += 1;
+        for signatory in self.signatories.iter_mut() {
+            signatory.signature = None;
+        }
+        self.history.push(ContractEvent::new(
+            ContractEventKind::Amended { version: self.version },
+            proposed_by,
+        ));
+        self.version
+    }
+
+    /// Set or clear the signature of a signatory, recording the change in the history under actor.
+    /// Unknown signatories and clearing a missing signature are no-ops.
+    pub fn set_signature(&mut self, signer: &SignatoryId, signature: Option<ContractSignature>, actor: Principal) {
+        let Some(signatory) = self.signatories.iter_mut().find(|s| s.is(signer)) else {
+            return;
+        };
+
+        if !signatory.has_signed() && signature.is_none() {
+            return;
+        }
+
+        let kind = if signature.is_some() {
+            ContractEventKind::Signed
+        } else {
+            ContractEventKind::SignatureRevoked
+        };
+        signatory.signature = signature;
+        self.history.push(ContractEvent::new(kind, actor));
+    }
+
+    pub fn signatory(&self, principal: &Principal) -> Option<&Signatory> {
+        self.signatories.iter().find(|s| s.principal.as_ref() == Some(principal))
+    }
+
+    pub fn evm_signatory(&self, address: &str) -> Option<&Signatory> {
+        self.signatories.iter().find(|s| s.evm_address.as_deref() == Some(address))
+    }
+
+    pub fn is_signatory(&self, principal: &Principal) -> bool {
+        self.signatory(principal).is_some()
+    }
+
+    pub fn has_role(&self, principal: &Principal, role: SignatoryRole) -> bool {
+        self.signatory(principal).is_some_and(|s| s.role == role)
+    }
+
+    /// Check a content hash provided by a client against the current version
+    pub fn matches_content_hash(&self, content_hash: &str) -> bool {
+        self.content_hash.eq_ignore_ascii_case(content_hash)
+    }
+
+    /// Whether the signatures collected so far satisfy the contract's quorum
     pub fn is_signed(&self) -> bool {
-        self.signatories.buyer.1 && self.signatories.seller.1
+        self.quorum.is_met(&self.signatories)
     }
 
     pub fn issued_payment(&self) -> bool {
         self.issued_payment
     }
+
+    pub fn status(&self) -> ContractStatus {
+        if self.issued_payment {
+            ContractStatus::Paid
+        } else if self.is_signed() {
+            ContractStatus::Signed
+        } else if self.signatories.iter().any(Signatory::has_signed) {
+            ContractStatus::PartiallySigned
+        } else {
+            ContractStatus::Draft
+        }
+    }
+
+    /// Candid encoding of the contract, the bytes hashed into the certified tree
+    pub fn encoded(&self) -> Vec<u8> {
+        Encode!(self).unwrap()
+    }
+
+    /// Hash of the contract in the certified tree: sha256 of its candid encoding
+    pub fn certified_hash(&self) -> Hash {
+        Sha256::digest(self.encoded()).into()
+    }
+
+    pub fn certified(&self) -> CertifiedHash {
+        CertifiedHash {
+            hash: self.certified_hash(),
+            is_signed: self.is_signed(),
+        }
+    }
 }
@@ -0,0 +1,115 @@
+use candid::{CandidType, Deserialize};
+use candid::Principal;
+use super::{uuid::Uuid, Contract, ContractStatus, ContractTerms, PublicSummaryConfig, SignatoryRole};
+
+pub const DEFAULT_CONTRACTS_PAGE_SIZE: u32 = 20;
+pub const MAX_CONTRACTS_PAGE_SIZE: u32 = 100;
+
+#[derive(Clone, Copy, Debug, Default, CandidType, Deserialize)]
+pub enum ContractSort {
+    #[default]
+    CreatedAtDesc,
+    CreatedAtAsc,
+}
+
+/// Filters, sorting and cursor for listing the contracts of a participant.
+/// All filters are optional, created_from and created_to are inclusive.
+#[derive(Clone, Debug, Default, CandidType, Deserialize)]
+pub struct ListContractsRequest {
+    pub role: Option<SignatoryRole>,
+    pub status: Option<ContractStatus>,
+    pub created_from: Option<u64>,
+    pub created_to: Option<u64>,
+    pub sort: Option<ContractSort>,
+    /// next_cursor of the previous page
+    pub cursor: Option<ContractCursor>,
+    pub limit: Option<u32>,
+}
+
+/// Position of a contract in creation order.
+/// A page continues after the cursor even if the contract it points to no longer matches the filters.
+#[derive(Clone, Copy, Debug, CandidType, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ContractCursor {
+    pub created_at: u64,
+    pub contract_id: Uuid,
+}
+
+impl ContractCursor {
+    pub fn key(&self) -> (u64, Uuid) {
+        (self.created_at, self.contract_id)
+    }
+}
+
+/// State of a contract as listed in a page, without its json and history, see get_contract
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct ContractListItem {
+    pub contract_id: Uuid,
+    pub role: SignatoryRole,
+    pub status: ContractStatus,
+    pub version: u32,
+    pub content_hash: String,
+    pub created_at: u64,
+    pub terms: ContractTerms,
+    pub encrypted: bool,
+}
+
+impl ContractListItem {
+    pub fn new(contract_id: Uuid, role: SignatoryRole, contract: &Contract) -> Self {
+        Self {
+            contract_id,
+            role,
+            status: contract.status(),
+            version: contract.version,
+            content_hash: contract.content_hash.clone(),
+            created_at: contract.created_at,
+            terms: contract.terms.clone(),
+            encrypted: contract.is_encrypted(),
+        }
+    }
+
+    pub fn cursor(&self) -> ContractCursor {
+        ContractCursor {
+            created_at: self.created_at,
+            contract_id: self.contract_id,
+        }
+    }
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct ContractPage {
+    pub contracts: Vec<ContractListItem>,
+    /// cursor for the next page, none if this is the last one
+    pub next_cursor: Option<ContractCursor>,
+}
+
+/// Non-sensitive view of a contract, readable by anyone when enabled in the config.
+/// Optional fields are only set if the config includes them.
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct ContractSummary {
+    pub contract_id: Uuid,
+    pub status: ContractStatus,
+    pub version: u32,
+    pub created_at: u64,
+    pub content_hash: Option<String>,
+    /// principal, Ethereum address, role and whether it has signed the current version
+    pub signatories: Option<Vec<(Option<Principal>, Option<String>, SignatoryRole, bool)>>,
+}
+
+impl ContractSummary {
+    pub fn new(contract_id: Uuid, contract: &Contract, config: &PublicSummaryConfig) -> Self {
+        Self {
+            contract_id,
+            status: contract.status(),
+            version: contract.version,
+            created_at: contract.created_at,
+            content_hash: config.include_content_hash.then(|| contract.content_hash.clone()),
+            signatories: config.include_signatories.then(|| {
+                contract
+                    .signatories
+                    .iter()
+                    .map(|s| (s.principal, s.evm_address.clone(), s.role, s.has_signed()))
+                    .collect()
+            }),
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use std::borrow::Cow;
+use candid::{CandidType, Decode, Deserialize, Encode, Principal};
+use ic_stable_structures::{storable::Bound, Storable};
+use super::{result::ApiError, Uuid};
+
+pub const MAX_MESSAGE_SIZE: usize = 4 * 1024;
+pub const DEFAULT_MESSAGES_PAGE_SIZE: u32 = 50;
+pub const MAX_MESSAGES_PAGE_SIZE: u32 = 200;
+
+/// A message in the thread of a contract. Messages are append-only and numbered per contract.
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct ContractMessage {
+    pub index: u64,
+    pub author: Principal,
+    pub timestamp: u64,
+    pub text: String,
+    /// an attachment of the same contract the message refers to
+    pub attachment_id: Option<Uuid>,
+}
+
+impl Storable for ContractMessage {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl ContractMessage {
+    pub fn new(index: u64, author: Principal, text: String, attachment_id: Option<Uuid>) -> Self {
+        Self {
+            index,
+            author,
+            timestamp: ic_cdk::api::time(),
+            text,
+            attachment_id,
+        }
+    }
+
+    pub fn validate_text(text: &str) -> Result<(), ApiError> {
+        if text.trim().is_empty() {
+            return Err(ApiError::invalid_argument("Message must not be empty"));
+        }
+
+        if text.len() > MAX_MESSAGE_SIZE {
+            return Err(ApiError::invalid_argument(&format!(
+                "Message exceeds the maximum size of {} bytes",
+                MAX_MESSAGE_SIZE
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct MessagePage {
+    /// messages in the order they were posted
+    pub messages: Vec<ContractMessage>,
+    /// index to pass as start for the next page, none if this is the last one
+    pub next_index: Option<u64>,
+}
\ No newline at end of file
@@ -0,0 +1,75 @@
+use std::borrow::Cow;
+use candid::{CandidType, Decode, Deserialize, Encode, Principal};
+use ic_stable_structures::{storable::Bound, Storable};
+use jsonschema::JSONSchema;
+use serde_json::Value;
+use super::result::ApiError;
+
+/// A JSON Schema registered by an admin, used to validate the json of new contracts.
+/// Schemas are versioned, the latest registered version is the active one.
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct ContractSchema {
+    pub version: u32,
+    pub schema_json: String,
+    pub registered_by: Principal,
+    pub registered_at: u64,
+}
+
+impl Storable for ContractSchema {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl ContractSchema {
+    pub fn new(version: u32, schema_json: String, registered_by: Principal) -> Self {
+        Self {
+            version,
+            schema_json,
+            registered_by,
+            registered_at: ic_cdk::api::time(),
+        }
+    }
+
+    /// Parse the schema json and check that it is a valid JSON Schema
+    pub fn compile(schema_json: &str) -> Result<JSONSchema, ApiError> {
+        let schema: Value = serde_json::from_str(schema_json).map_err(|e| {
+            ApiError::invalid_argument(&format!("Schema is not valid json: {}", e))
+        })?;
+
+        JSONSchema::compile(&schema).map_err(|e| {
+            ApiError::invalid_argument(&format!("Schema is not a valid JSON Schema: {}", e))
+        })
+    }
+
+    /// Validate a parsed contract json against this schema, listing every violation
+    pub fn validate(&self, contract: &Value) -> Result<(), ApiError> {
+        validate_json(&self.schema_json, contract).map_err(|violations| {
+            ApiError::invalid_argument(&format!(
+                "Contract json does not match schema version {}: {}",
+                self.version,
+                violations.join("; ")
+            ))
+        })
+    }
+}
+
+/// Validate a json value against a JSON Schema, returning the list of violations.
+/// A schema that does not compile is reported as a single violation.
+pub fn validate_json(schema_json: &str, value: &Value) -> Result<(), Vec<String>> {
+    let schema = ContractSchema::compile(schema_json).map_err(|e| vec![e.message().to_string()])?;
+
+    if let Err(errors) = schema.validate(value) {
+        return Err(errors
+            .map(|e| format!("{}: {}", e.instance_path, e))
+            .collect());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,188 @@
+use std::borrow::Cow;
+use candid::{CandidType, Decode, Deserialize, Encode, Principal};
+use ic_stable_structures::{storable::Bound, Storable};
+use serde_json::{Map, Value};
+use super::{result::ApiError, uuid::Uuid, validate_json, ContractSchema};
+
+/// A versioned contract template registered by an admin.
+///
+/// The body is a contract json in which string values of the form `"{{name}}"` are placeholders,
+/// filled from the params given on contract creation or from the template defaults.
+/// The rendered json has to match the template schema.
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct ContractTemplate {
+    pub template_id: Uuid,
+    pub version: u32,
+    pub name: String,
+    pub schema_json: String,
+    pub body_json: String,
+    /// json object with default values for placeholders
+    pub defaults_json: String,
+    /// placeholder names found in the body
+    pub placeholders: Vec<String>,
+    pub registered_by: Principal,
+    pub registered_at: u64,
+}
+
+impl Storable for ContractTemplate {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// Template version a contract json was rendered from
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct TemplateReference {
+    pub template_id: Uuid,
+    pub version: u32,
+}
+
+impl ContractTemplate {
+    /// Build a template version, checking that schema, body and defaults are consistent
+    pub fn new(
+        template_id: Uuid,
+        version: u32,
+        name: String,
+        schema_json: String,
+        body_json: String,
+        defaults_json: Option<String>,
+        registered_by: Principal,
+    ) -> Result<Self, ApiError> {
+        ContractSchema::compile(&schema_json)?;
+
+        let body = parse_json(&body_json, "Template body")?;
+        let mut placeholders = vec![];
+        collect_placeholders(&body, &mut placeholders);
+
+        let defaults_json = defaults_json.unwrap_or_else(|| "{}".to_string());
+        let defaults = parse_object(&defaults_json, "Template defaults")?;
+        if let Some(key) = defaults.keys().find(|key| !placeholders.contains(*key)) {
+            return Err(ApiError::invalid_argument(&format!(
+                "Default value given for unknown placeholder {}",
+                key
+            )));
+        }
+
+        Ok(Self {
+            template_id,
+            version,
+            name,
+            schema_json,
+            body_json,
+            defaults_json,
+            placeholders,
+            registered_by,
+            registered_at: ic_cdk::api::time(),
+        })
+    }
+
+    pub fn reference(&self) -> TemplateReference {
+        TemplateReference {
+            template_id: self.template_id,
+            version: self.version,
+        }
+    }
+
+    /// Fill the placeholders of the body with the given params, falling back to the defaults,
+    /// and validate the result against the template schema.
+    /// Params that match no placeholder are rejected.
+    pub fn render(&self, params_json: &str) -> Result<String, ApiError> {
+        let params = parse_object(params_json, "Template params")?;
+        let defaults = parse_object(&self.defaults_json, "Template defaults")?;
+
+        let unknown: Vec<&str> = params
+            .keys()
+            .filter(|key| !self.placeholders.contains(*key))
+            .map(String::as_str)
+            .collect();
+        if !unknown.is_empty() {
+            return Err(ApiError::invalid_argument(&format!(
+                "Params given for unknown template placeholders: {}",
+                unknown.join(", ")
+            )));
+        }
+
+        let missing: Vec<&str> = self
+            .placeholders
+            .iter()
+            .filter(|name| !params.contains_key(*name) && !defaults.contains_key(*name))
+            .map(String::as_str)
+            .collect();
+        if !missing.is_empty() {
+            return Err(ApiError::invalid_argument(&format!(
+                "Missing values for template placeholders: {}",
+                missing.join(", ")
+            )));
+        }
+
+        let mut contract = parse_json(&self.body_json, "Template body")?;
+        fill_placeholders(&mut contract, &params, &defaults);
+
+        validate_json(&self.schema_json, &contract).map_err(|violations| {
+            ApiError::invalid_argument(&format!(
+                "Rendered contract does not match template {} version {}: {}",
+                self.template_id,
+                self.version,
+                violations.join("; ")
+            ))
+        })?;
+
+        Ok(contract.to_string())
+    }
+}
+
+fn parse_json(json: &str, label: &str) -> Result<Value, ApiError> {
+    serde_json::from_str(json)
+        .map_err(|e| ApiError::invalid_argument(&format!("{} is not valid json: {}", label, e)))
+}
+
+fn parse_object(json: &str, label: &str) -> Result<Map<String, Value>, ApiError> {
+    match parse_json(json, label)? {
+        Value::Object(map) => Ok(map),
+        _ => Err(ApiError::invalid_argument(&format!("{} must be a json object", label))),
+    }
+}
+
+fn placeholder_name(value: &str) -> Option<&str> {
+    value
+        .strip_prefix("{{")
+        .and_then(|v| v.strip_suffix("}}"))
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+}
+
+fn collect_placeholders(value: &Value, placeholders: &mut Vec<String>) {
+    match value {
+        Value::String(s) => {
+            if let Some(name) = placeholder_name(s) {
+                if !placeholders.iter().any(|p| p == name) {
+                    placeholders.push(name.to_string());
+                }
+            }
+        }
+        Value::Array(items) => items.iter().for_each(|item| collect_placeholders(item, placeholders)),
+        Value::Object(map) => map.values().for_each(|item| collect_placeholders(item, placeholders)),
+        _ => {}
+    }
+}
+
+fn fill_placeholders(value: &mut Value, params: &Map<String, Value>, defaults: &Map<String, Value>) {
+    match value {
+        Value::String(s) => {
+            if let Some(name) = placeholder_name(s) {
+                if let Some(replacement) = params.get(name).or_else(|| defaults.get(name)) {
+                    *value = replacement.clone();
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| fill_placeholders(item, params, defaults)),
+        Value::Object(map) => map.values_mut().for_each(|item| fill_placeholders(item, params, defaults)),
+        _ => {}
+    }
+}
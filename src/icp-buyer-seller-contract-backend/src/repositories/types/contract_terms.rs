@@ -0,0 +1,155 @@
+use candid::{CandidType, Deserialize};
+use serde_json::Value;
+use super::result::ApiError;
+
+const NS_PER_S: u64 = 1_000_000_000;
+const NS_PER_DAY: u64 = 24 * 60 * 60 * NS_PER_S;
+
+/// International commercial terms defining the delivery obligations of the parties
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Copy, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub enum Incoterm {
+    EXW,
+    FCA,
+    CPT,
+    CIP,
+    DAP,
+    DPU,
+    DDP,
+    FAS,
+    FOB,
+    CFR,
+    CIF,
+}
+
+impl TryFrom<&str> for Incoterm {
+    type Error = ApiError;
+
+    fn try_from(incoterm: &str) -> Result<Self, Self::Error> {
+        match incoterm.trim().to_ascii_uppercase().as_str() {
+            "EXW" => Ok(Incoterm::EXW),
+            "FCA" => Ok(Incoterm::FCA),
+            "CPT" => Ok(Incoterm::CPT),
+            "CIP" => Ok(Incoterm::CIP),
+            "DAP" => Ok(Incoterm::DAP),
+            "DPU" => Ok(Incoterm::DPU),
+            "DDP" => Ok(Incoterm::DDP),
+            "FAS" => Ok(Incoterm::FAS),
+            "FOB" => Ok(Incoterm::FOB),
+            "CFR" => Ok(Incoterm::CFR),
+            "CIF" => Ok(Incoterm::CIF),
+            _ => Err(ApiError::invalid_argument(&format!("Unknown incoterm: {}", incoterm))),
+        }
+    }
+}
+
+/// Key terms of a contract, extracted from the contract json whenever it is created or amended.
+///
+/// The terms are read from these json fields, each of them optional:
+/// - `price.amount`: integer amount in the smallest unit of the currency, e.g. 1 USDC = 1000000
+/// - `price.currency`: currency code, e.g. "USDC"
+/// - `goods.quantity`: number of units of the goods
+/// - `delivery_date`: date as "YYYY-MM-DD" or RFC 3339 date-time
+/// - `incoterms`: incoterm code, e.g. "FOB"
+/// - `inspection_days`: days the buyer has to inspect the goods after delivery
+#[derive(Clone, Debug, Default, CandidType, Deserialize)]
+pub struct ContractTerms {
+    pub price: Option<u64>,
+    pub currency: Option<String>,
+    pub quantity: Option<f64>,
+    /// delivery date in nanoseconds since the unix epoch
+    pub delivery_date: Option<u64>,
+    pub incoterm: Option<Incoterm>,
+    pub inspection_days: Option<u32>,
+}
+
+impl ContractTerms {
+    pub fn from_json(contract: &Value) -> Result<Self, ApiError> {
+        let price = contract.get("price");
+
+        Ok(Self {
+            price: field(price, "amount", "price.amount", Value::as_u64)?,
+            currency: field(price, "currency", "price.currency", |v| v.as_str().map(str::to_string))?,
+            quantity: field(contract.get("goods"), "quantity", "goods.quantity", Value::as_f64)?,
+            delivery_date: field(Some(contract), "delivery_date", "delivery_date", Value::as_str)?
+                .map(parse_date)
+                .transpose()?,
+            incoterm: field(Some(contract), "incoterms", "incoterms", Value::as_str)?
+                .map(Incoterm::try_from)
+                .transpose()?,
+            inspection_days: field(Some(contract), "inspection_days", "inspection_days", |v| {
+                v.as_u64().and_then(|days| u32::try_from(days).ok())
+            })?,
+        })
+    }
+
+    /// End of the UTC day of the delivery date; the contract can be signed until then.
+    /// A delivery date given as "YYYY-MM-DD" is stored as midnight, so comparing against it directly
+    /// would close signing at the start of the delivery day.
+    pub fn signing_deadline(&self) -> Option<u64> {
+        let delivery_date = self.delivery_date?;
+
+        Some((delivery_date / NS_PER_DAY + 1).saturating_mul(NS_PER_DAY))
+    }
+
+    /// End of the inspection period: the delivery date plus the inspection days
+    pub fn inspection_deadline(&self) -> Option<u64> {
+        let delivery_date = self.delivery_date?;
+        let inspection_days = self.inspection_days.unwrap_or(0) as u64;
+
+        Some(delivery_date.saturating_add(inspection_days.saturating_mul(NS_PER_DAY)))
+    }
+}
+
+/// Read an optional field of a json object, failing if it is present with an unexpected type
+fn field<'a, T>(
+    parent: Option<&'a Value>,
+    key: &str,
+    path: &str,
+    extract: impl Fn(&'a Value) -> Option<T>,
+) -> Result<Option<T>, ApiError> {
+    match parent.and_then(|p| p.get(key)) {
+        None | Some(Value::Null) => Ok(None),
+        Some(value) => extract(value)
+            .map(Some)
+            .ok_or_else(|| ApiError::invalid_argument(&format!("Contract term {} has an invalid value: {}", path, value))),
+    }
+}
+
+fn parse_date(date: &str) -> Result<u64, ApiError> {
+    let timestamp_s = chrono::DateTime::parse_from_rfc3339(date)
+        .map(|date_time| date_time.timestamp())
+        .or_else(|_| {
+            chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .map(|date| date.and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp())
+        })
+        .map_err(|_| ApiError::invalid_argument(&format!("Contract term delivery_date is not a valid date: {}", date)))?;
+
+    let timestamp_s = u64::try_from(timestamp_s)
+        .map_err(|_| ApiError::invalid_argument(&format!("Contract term delivery_date is before 1970: {}", date)))?;
+
+    timestamp_s.checked_mul(NS_PER_S)
+        .ok_or_else(|| ApiError::invalid_argument(&format!("Contract term delivery_date is too far in the future: {}", date)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_date_rejects_dates_beyond_the_nanosecond_range() {
+        assert_eq!(parse_date("2024-03-01").unwrap(), 1_709_251_200 * NS_PER_S);
+        assert!(parse_date("9999-12-31").is_err());
+    }
+
+    #[test]
+    fn inspection_deadline_saturates() {
+        let terms = ContractTerms {
+            delivery_date: Some(NS_PER_DAY),
+            inspection_days: Some(u32::MAX),
+            ..Default::default()
+        };
+
+        assert_eq!(terms.inspection_deadline(), Some(u64::MAX));
+    }
+}
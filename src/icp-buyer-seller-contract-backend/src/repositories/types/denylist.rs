@@ -0,0 +1,42 @@
+use std::borrow::Cow;
+use candid::{CandidType, Decode, Deserialize, Encode, Principal};
+use ic_stable_structures::{storable::Bound, Storable};
+
+pub const MAX_DENYLIST_IMPORT_SIZE: usize = 1000;
+pub const MAX_DENYLIST_REASON_SIZE: usize = 200;
+
+/// A principal or Ethereum address that must not trade or receive payments
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub enum DenylistSubject {
+    Principal(Principal),
+    EvmAddress(String),
+}
+
+impl std::fmt::Display for DenylistSubject {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DenylistSubject::Principal(principal) => write!(f, "principal {}", principal.to_text()),
+            DenylistSubject::EvmAddress(address) => write!(f, "address {}", address),
+        }
+    }
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct DenylistEntry {
+    /// e.g. the sanctions list the subject appears on
+    pub reason: String,
+    pub added_by: Principal,
+    pub added_at: u64,
+}
+
+impl Storable for DenylistEntry {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
\ No newline at end of file
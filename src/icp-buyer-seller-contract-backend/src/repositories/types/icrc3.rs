@@ -0,0 +1,247 @@
+use std::borrow::Cow;
+use candid::{CandidType, Decode, Deserialize, Encode, Int, Nat, Principal};
+use ic_stable_structures::{storable::Bound, Storable};
+use serde_bytes::ByteBuf;
+use sha2::{Digest, Sha256};
+
+pub type Hash = [u8; 32];
+
+pub const MAX_BLOCKS_PER_REQUEST: u64 = 100;
+
+/// Generic value of the ICRC-3 block schema
+#[derive(Clone, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub enum ICRC3Value {
+    Blob(ByteBuf),
+    Text(String),
+    Nat(Nat),
+    Int(Int),
+    Array(Vec<ICRC3Value>),
+    Map(Vec<(String, ICRC3Value)>),
+}
+
+impl Storable for ICRC3Value {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+impl From<&str> for ICRC3Value {
+    fn from(text: &str) -> Self {
+        Self::Text(text.to_string())
+    }
+}
+
+impl From<String> for ICRC3Value {
+    fn from(text: String) -> Self {
+        Self::Text(text)
+    }
+}
+
+impl From<u64> for ICRC3Value {
+    fn from(nat: u64) -> Self {
+        Self::Nat(Nat::from(nat))
+    }
+}
+
+impl From<Principal> for ICRC3Value {
+    fn from(principal: Principal) -> Self {
+        Self::Blob(ByteBuf::from(principal.as_slice().to_vec()))
+    }
+}
+
+impl From<Hash> for ICRC3Value {
+    fn from(hash: Hash) -> Self {
+        Self::Blob(ByteBuf::from(hash.to_vec()))
+    }
+}
+
+impl ICRC3Value {
+    /// Representation-independent hash of the value, as specified by ICRC-3
+    pub fn hash(&self) -> Hash {
+        match self {
+            Self::Blob(bytes) => Sha256::digest(bytes).into(),
+            Self::Text(text) => Sha256::digest(text.as_bytes()).into(),
+            Self::Nat(nat) => {
+                let mut buf = vec![];
+                nat.encode(&mut buf).expect("failed to leb128 encode nat");
+                Sha256::digest(&buf).into()
+            }
+            Self::Int(int) => {
+                let mut buf = vec![];
+                int.encode(&mut buf).expect("failed to sleb128 encode int");
+                Sha256::digest(&buf).into()
+            }
+            Self::Array(values) => {
+                let mut hasher = Sha256::new();
+                for value in values {
+                    hasher.update(value.hash());
+                }
+                hasher.finalize().into()
+            }
+            Self::Map(entries) => {
+                let mut hashes: Vec<Vec<u8>> = entries
+                    .iter()
+                    .map(|(key, value)| {
+                        let mut pair = Sha256::digest(key.as_bytes()).to_vec();
+                        pair.extend_from_slice(&value.hash());
+                        pair
+                    })
+                    .collect();
+                hashes.sort();
+
+                let mut hasher = Sha256::new();
+                for pair in hashes {
+                    hasher.update(pair);
+                }
+                hasher.finalize().into()
+            }
+        }
+    }
+}
+
+/// Block types recorded by this canister. The contract ones share the same tx fields:
+/// contract_id, actor, version, content_hash and status.
+pub const BTYPE_CONTRACT_CREATED: &str = "contract_created";
+pub const BTYPE_CONTRACT_AMENDED: &str = "contract_amended";
+pub const BTYPE_CONTRACT_SIGNED: &str = "contract_signed";
+pub const BTYPE_SIGNATURE_REVOKED: &str = "signature_revoked";
+/// a payment issued for a contract, with the amount and the recipient address in the tx
+pub const BTYPE_PAYMENT_ISSUED: &str = "payment_issued";
+
+pub const SUPPORTED_BLOCK_TYPES: [&str; 5] = [
+    BTYPE_CONTRACT_CREATED,
+    BTYPE_CONTRACT_AMENDED,
+    BTYPE_CONTRACT_SIGNED,
+    BTYPE_SIGNATURE_REVOKED,
+    BTYPE_PAYMENT_ISSUED,
+];
+
+/// Build a block in the generic ICRC-3 format; phash is the hash of the previous block, if any
+pub fn new_block(btype: &str, phash: Option<Hash>, tx: Vec<(String, ICRC3Value)>) -> ICRC3Value {
+    let mut block = vec![
+        ("btype".to_string(), ICRC3Value::from(btype)),
+        ("ts".to_string(), ICRC3Value::from(ic_cdk::api::time())),
+        ("tx".to_string(), ICRC3Value::Map(tx)),
+    ];
+    if let Some(phash) = phash {
+        block.push(("phash".to_string(), ICRC3Value::from(phash)));
+    }
+
+    ICRC3Value::Map(block)
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct GetBlocksArgs {
+    pub start: Nat,
+    pub length: Nat,
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct BlockWithId {
+    pub id: Nat,
+    pub block: ICRC3Value,
+}
+
+candid::define_function!(pub GetBlocksFn : (Vec<GetBlocksArgs>) -> (GetBlocksResult) query);
+
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct ArchivedBlocks {
+    pub args: Vec<GetBlocksArgs>,
+    pub callback: GetBlocksFn,
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct GetBlocksResult {
+    pub log_length: Nat,
+    pub blocks: Vec<BlockWithId>,
+    /// always empty, blocks are not archived
+    pub archived_blocks: Vec<ArchivedBlocks>,
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct GetArchivesArgs {
+    pub from: Option<Principal>,
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct ICRC3ArchiveInfo {
+    pub canister_id: Principal,
+    pub start: Nat,
+    pub end: Nat,
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct ICRC3DataCertificate {
+    pub certificate: ByteBuf,
+    pub hash_tree: ByteBuf,
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct SupportedBlockType {
+    pub block_type: String,
+    pub url: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blob(bytes: &[u8]) -> ICRC3Value {
+        ICRC3Value::Blob(ByteBuf::from(bytes.to_vec()))
+    }
+
+    fn assert_hash(value: ICRC3Value, expected: &str) {
+        assert_eq!(hex::encode(value.hash()), expected, "{:?}", value);
+    }
+
+    // reference values from the ICRC-3 standard
+
+    #[test]
+    fn hashes_nat() {
+        assert_hash(ICRC3Value::Nat(Nat::from(42u64)), "684888c0ebb17f374298b65ee2807526c066094c701bcc7ebbe1c1095f494fc1");
+    }
+
+    #[test]
+    fn hashes_int() {
+        assert_hash(ICRC3Value::Int(Int::from(-42i64)), "de5a6f78116eca62d7fc5ce159d23ae6b889b365a1739ad2cf36f925a140d0cc");
+    }
+
+    #[test]
+    fn hashes_text() {
+        assert_hash(ICRC3Value::from("Hello, World!"), "dffd6021bb2bd5b0af676290809ec3a53191dd81c7f70a4b28688a362182986f");
+    }
+
+    #[test]
+    fn hashes_blob() {
+        assert_hash(blob(&[1, 2, 3, 4]), "9f64a747e1b97f131fabb6b447296c9b6f0201e79fb3c5356e6c77e89b6a806a");
+    }
+
+    #[test]
+    fn hashes_array() {
+        let array = ICRC3Value::Array(vec![ICRC3Value::from(3u64), ICRC3Value::from("foo"), blob(&[5, 6])]);
+
+        assert_hash(array, "514a04011caa503990d446b7dec5d79e19c221ae607fb08b2848c67734d468d6");
+    }
+
+    #[test]
+    fn hashes_map() {
+        let mut entries = vec![
+            ("from".to_string(), blob(&hex::decode("00abcdef0012340056789a00bcdef000012345678900abcdef01").unwrap())),
+            ("to".to_string(), blob(&hex::decode("00ab0def0012340056789a00bcdef000012345678900abcdef01").unwrap())),
+            ("amount".to_string(), ICRC3Value::from(42u64)),
+            ("created_at".to_string(), ICRC3Value::from(1699218263u64)),
+            ("memo".to_string(), ICRC3Value::from(0u64)),
+        ];
+        let expected = "c56ece650e1de4269c5bdeff7875949e3e2033f85b2d193c2ff4f7f78bdcfc75";
+
+        assert_hash(ICRC3Value::Map(entries.clone()), expected);
+        entries.reverse();
+        assert_hash(ICRC3Value::Map(entries), expected);
+    }
+}
\ No newline at end of file
@@ -1,9 +1,41 @@
+mod attachment;
+mod attestation;
+mod audit;
+mod certified;
+mod config;
 mod contract;
+mod contract_list;
+mod contract_message;
+mod content_hash;
+mod contract_schema;
+mod contract_template;
+mod contract_terms;
+mod denylist;
+mod icrc3;
+mod profile;
 mod result;
+mod siwe;
 mod uuid;
 mod user;
+mod verification;
 
+pub use attachment::*;
+pub use attestation::*;
+pub use audit::*;
+pub use certified::*;
+pub use config::*;
 pub use contract::*;
+pub use contract_list::*;
+pub use contract_message::*;
+pub use content_hash::*;
+pub use contract_schema::*;
+pub use contract_template::*;
+pub use contract_terms::*;
+pub use denylist::*;
+pub use icrc3::*;
+pub use profile::*;
 pub use result::*;
+pub use siwe::*;
 pub use uuid::*;
-pub use user::*;
\ No newline at end of file
+pub use user::*;
+pub use verification::*;
\ No newline at end of file
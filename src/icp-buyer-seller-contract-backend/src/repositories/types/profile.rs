@@ -0,0 +1,99 @@
+use std::borrow::Cow;
+use candid::{CandidType, Decode, Deserialize, Encode, Principal};
+use ic_stable_structures::{storable::Bound, Storable};
+use super::{ApiError, Contract};
+
+pub const MAX_PROFILE_FIELD_SIZE: usize = 200;
+pub const MAX_PAYOUT_ADDRESSES: usize = 5;
+
+/// Ethereum address a participant proved to control by signing `payout_address_message`
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct PayoutAddress {
+    /// checksummed Ethereum address
+    pub address: String,
+    pub verified_at: u64,
+}
+
+/// Self-managed profile of a buyer or seller, shown to the counterparties of its contracts
+#[derive(Clone, Debug, Default, CandidType, Deserialize)]
+pub struct Profile {
+    pub display_name: Option<String>,
+    pub company: Option<String>,
+    /// ISO 3166-1 alpha-2 country code
+    pub country: Option<String>,
+    /// email address or phone number
+    pub contact: Option<String>,
+    /// verified payout addresses, the first one is used when a payment is issued without an address
+    pub payout_addresses: Vec<PayoutAddress>,
+    pub updated_at: u64,
+}
+
+/// Fields of a profile participants can edit directly; payout addresses have to be verified
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct ProfileUpdate {
+    pub display_name: Option<String>,
+    pub company: Option<String>,
+    pub country: Option<String>,
+    pub contact: Option<String>,
+}
+
+impl ProfileUpdate {
+    pub fn validate(&self) -> Result<(), ApiError> {
+        for (name, value) in [("Display name", &self.display_name), ("Company", &self.company), ("Contact", &self.contact)] {
+            if value.as_ref().is_some_and(|value| value.trim().is_empty() || value.len() > MAX_PROFILE_FIELD_SIZE) {
+                return Err(ApiError::invalid_argument(&format!(
+                    "{} must be between 1 and {} bytes",
+                    name, MAX_PROFILE_FIELD_SIZE
+                )));
+            }
+        }
+
+        if self.country.as_ref().is_some_and(|country| country.len() != 2 || !country.chars().all(|c| c.is_ascii_uppercase())) {
+            return Err(ApiError::invalid_argument("Country must be an ISO 3166-1 alpha-2 code, e.g. DE"));
+        }
+
+        Ok(())
+    }
+}
+
+impl Profile {
+    pub fn apply(&mut self, update: ProfileUpdate) {
+        self.display_name = update.display_name;
+        self.company = update.company;
+        self.country = update.country;
+        self.contact = update.contact;
+        self.updated_at = ic_cdk::api::time();
+    }
+
+    pub fn has_payout_address(&self, address: &str) -> bool {
+        self.payout_addresses.iter().any(|payout| payout.address == address)
+    }
+}
+
+impl Storable for Profile {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// Message a principal's payout address has to sign with personal_sign
+pub fn payout_address_message(address: &str, principal: &Principal) -> String {
+    format!(
+        "Register payout address on TradeOnChain\nAddress: {}\nPrincipal: {}",
+        address,
+        principal.to_text()
+    )
+}
+
+/// A contract with the profiles of its signatories, for those who have one
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct ContractDetails {
+    pub contract: Contract,
+    pub profiles: Vec<(Principal, Profile)>,
+}
\ No newline at end of file
@@ -58,6 +58,14 @@ impl ApiError {
         }
     }
 
+    /// The caller, a party or an address is on the denylist
+    pub fn blocked(message: &str) -> Self {
+        Self {
+            code: 451,
+            message: message.into(),
+        }
+    }
+
     pub fn internal(message: &str) -> Self {
         Self {
             code: 500,
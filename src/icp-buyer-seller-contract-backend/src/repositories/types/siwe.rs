@@ -0,0 +1,94 @@
+use candid::{CandidType, Deserialize, Principal};
+use serde_bytes::ByteBuf;
+
+/// How long a prepared SIWE message can be used to log in, in nanoseconds
+pub const SIWE_MESSAGE_TTL: u64 = 5 * 60 * 1_000_000_000;
+/// Upper bound for the number of prepared SIWE messages waiting for a login, the oldest is dropped beyond
+pub const MAX_PENDING_SIWE_MESSAGES: usize = 10_000;
+/// Upper bound for the number of prepared SIWE messages of one address, its oldest is dropped beyond
+pub const MAX_PENDING_SIWE_MESSAGES_PER_ADDRESS: usize = 3;
+/// Default lifetime of a delegation issued on login, in nanoseconds
+pub const DEFAULT_SIWE_SESSION_EXPIRES_IN: u64 = 8 * 60 * 60 * 1_000_000_000;
+/// Upper bound for the lifetime of a delegation, in nanoseconds
+pub const MAX_SIWE_SESSION_EXPIRES_IN: u64 = 30 * 24 * 60 * 60 * 1_000_000_000;
+
+/// EIP-4361 message an Ethereum account signs to log in, see `to_eip4361`
+#[derive(Clone, Debug)]
+pub struct SiweMessage {
+    pub domain: String,
+    /// checksummed Ethereum address
+    pub address: String,
+    pub statement: Option<String>,
+    pub uri: String,
+    pub chain_id: u64,
+    pub nonce: String,
+    /// IC time the message was prepared at
+    pub issued_at: u64,
+    pub expiration_time: u64,
+}
+
+impl SiweMessage {
+    pub fn is_expired(&self) -> bool {
+        ic_cdk::api::time() > self.expiration_time
+    }
+
+    /// Render the message in the EIP-4361 format the wallet shows to the user
+    pub fn to_eip4361(&self) -> String {
+        let mut message = format!(
+            "{} wants you to sign in with your Ethereum account:\n{}\n\n",
+            self.domain, self.address
+        );
+        if let Some(statement) = &self.statement {
+            message.push_str(&format!("{}\n\n", statement));
+        }
+        message.push_str(&format!(
+            "URI: {}\nVersion: 1\nChain ID: {}\nNonce: {}\nIssued At: {}\nExpiration Time: {}",
+            self.uri,
+            self.chain_id,
+            self.nonce,
+            rfc3339(self.issued_at),
+            rfc3339(self.expiration_time)
+        ));
+        message
+    }
+}
+
+fn rfc3339(timestamp_ns: u64) -> String {
+    let seconds = (timestamp_ns / 1_000_000_000) as i64;
+    let nanoseconds = (timestamp_ns % 1_000_000_000) as u32;
+    chrono::DateTime::from_timestamp(seconds, nanoseconds)
+        .map(|date_time| date_time.to_rfc3339_opts(chrono::SecondsFormat::Secs, true))
+        .unwrap_or_default()
+}
+
+/// A prepared SIWE message and its nonce, which identifies the message on login
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct SiwePreparedLogin {
+    /// EIP-4361 message to sign
+    pub message: String,
+    pub nonce: String,
+}
+
+/// Result of a SIWE login, the session key can then fetch its delegation with siwe_get_delegation
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct SiweLoginDetails {
+    /// expiration of the delegation, in nanoseconds since the unix epoch
+    pub expiration: u64,
+    /// DER encoded canister signature public key the user's principal is derived from
+    pub user_canister_pubkey: ByteBuf,
+}
+
+/// Delegation from the user's canister signature key to a session key, as defined by the IC interface spec
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct Delegation {
+    pub pubkey: ByteBuf,
+    pub expiration: u64,
+    pub targets: Option<Vec<Principal>>,
+}
+
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct SignedDelegation {
+    pub delegation: Delegation,
+    /// CBOR encoded canister signature
+    pub signature: ByteBuf,
+}
\ No newline at end of file
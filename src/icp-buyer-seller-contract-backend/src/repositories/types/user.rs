@@ -2,30 +2,119 @@ use candid::{CandidType, Decode, Deserialize, Encode, Principal};
 use ic_stable_structures::{storable::Bound, Storable};
 use std::borrow::Cow;
 
-#[derive(Clone, Debug, CandidType, Deserialize)]
+/// A privileged action, granted to principals through their roles.
+/// Actions on an existing contract (amending, signing, attachments, messages, encryption keys)
+/// are gated by being a signatory of the contract instead, see the README.
+#[derive(Clone, Copy, Debug, CandidType, Deserialize, PartialEq, Eq)]
+pub enum Permission {
+    /// grant and revoke roles
+    UsersManage,
+    ConfigWrite,
+    /// register contract schemas and templates
+    SchemasManage,
+    /// create contracts, for any set of signatories
+    ContractsCreate,
+    /// read contracts the principal is not a signatory of
+    ContractsReadAll,
+    ContractsAnchor,
+    PaymentsIssue,
+    /// read the audit log and profile of any principal
+    AuditRead,
+    VerificationsRead,
+    VerificationsManage,
+    DenylistManage,
+}
+
+impl Permission {
+    pub const ALL: [Permission; 11] = [
+        Permission::UsersManage,
+        Permission::ConfigWrite,
+        Permission::SchemasManage,
+        Permission::ContractsCreate,
+        Permission::ContractsReadAll,
+        Permission::ContractsAnchor,
+        Permission::PaymentsIssue,
+        Permission::AuditRead,
+        Permission::VerificationsRead,
+        Permission::VerificationsManage,
+        Permission::DenylistManage,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Permission::UsersManage => "users.manage",
+            Permission::ConfigWrite => "config.write",
+            Permission::SchemasManage => "schemas.manage",
+            Permission::ContractsCreate => "contracts.create",
+            Permission::ContractsReadAll => "contracts.read_all",
+            Permission::ContractsAnchor => "contracts.anchor",
+            Permission::PaymentsIssue => "payments.issue",
+            Permission::AuditRead => "audit.read",
+            Permission::VerificationsRead => "verifications.read",
+            Permission::VerificationsManage => "verifications.manage",
+            Permission::DenylistManage => "denylist.manage",
+        }
+    }
+}
+
+/// A named set of permissions
+#[derive(Clone, Copy, Debug, CandidType, Deserialize, PartialEq, Eq)]
 pub enum Role{
     Admin,
-    FrontendServer
+    FrontendServer,
+    Arbitrator,
+    /// manages the verification records of participants
+    ComplianceOfficer,
+}
+
+impl Role {
+    pub fn permissions(&self) -> &'static [Permission] {
+        match self {
+            Role::Admin => &Permission::ALL,
+            Role::FrontendServer => &[Permission::ContractsCreate, Permission::ContractsReadAll, Permission::PaymentsIssue],
+            Role::Arbitrator => &[Permission::ContractsReadAll],
+            Role::ComplianceOfficer => &[
+                Permission::VerificationsRead,
+                Permission::VerificationsManage,
+                Permission::DenylistManage,
+            ],
+        }
+    }
 }
 
 #[derive(Clone, Debug, CandidType, Deserialize)]
 pub struct User {
-    pub role: Role,
+    pub roles: Vec<Role>,
     //this could be used to gate certain endpoints only to users authorized by the frontend to ensure they are registered.
 }
 
 impl User {
     pub fn new(role: Role) -> Self {
-        Self { role }
+        Self { roles: vec![role] }
+    }
+
+    pub fn has_role(&self, role: Role) -> bool {
+        self.roles.contains(&role)
     }
 
     pub fn is_admin(&self) -> bool {
-        matches!(self.role, Role::Admin)
+        self.has_role(Role::Admin)
     }
 
-    pub fn is_frontend_server(&self) -> bool {
-        matches!(self.role, Role::FrontendServer)
+    pub fn has_permission(&self, permission: Permission) -> bool {
+        self.roles.iter().any(|role| role.permissions().contains(&permission))
     }
+
+    /// Permissions of all roles of the user, without duplicates
+    pub fn permissions(&self) -> Vec<Permission> {
+        Permission::ALL.into_iter().filter(|permission| self.has_permission(*permission)).collect()
+    }
+}
+
+/// Users stored before principals could hold several roles
+#[derive(CandidType, Deserialize)]
+struct LegacyUser {
+    role: Role,
 }
 
 impl Storable for User {
@@ -34,7 +123,10 @@ impl Storable for User {
     }
 
     fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
-        Decode!(bytes.as_ref(), Self).unwrap()
+        Decode!(bytes.as_ref(), Self).unwrap_or_else(|_| {
+            let legacy = Decode!(bytes.as_ref(), LegacyUser).unwrap();
+            Self::new(legacy.role)
+        })
     }
 
     const BOUND: Bound = Bound::Unbounded;
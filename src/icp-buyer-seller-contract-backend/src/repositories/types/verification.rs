@@ -0,0 +1,64 @@
+use std::borrow::Cow;
+use candid::{CandidType, Decode, Deserialize, Encode, Principal};
+use ic_stable_structures::{storable::Bound, Storable};
+
+pub const MAX_PROVIDER_REFERENCE_SIZE: usize = 200;
+
+/// KYC level of a principal, higher levels include the lower ones
+#[derive(Clone, Copy, Debug, CandidType, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum VerificationLevel {
+    Basic,
+    Enhanced,
+}
+
+/// Verification record of a principal, set by a compliance officer
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct Verification {
+    pub level: VerificationLevel,
+    /// reference of the check at the KYC provider
+    pub provider_reference: String,
+    /// IC time after which the verification no longer counts, none if it does not expire
+    pub expires_at: Option<u64>,
+    pub verified_by: Principal,
+    pub verified_at: u64,
+}
+
+impl Verification {
+    pub fn is_expired(&self) -> bool {
+        self.expires_at.is_some_and(|expires_at| ic_cdk::api::time() > expires_at)
+    }
+
+    /// Whether the verification is current and at least of the given level
+    pub fn satisfies(&self, level: VerificationLevel) -> bool {
+        !self.is_expired() && self.level >= level
+    }
+}
+
+impl Storable for Verification {
+    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// Parties of contracts whose price is at least min_amount must be verified with at least level
+#[derive(Clone, Debug, CandidType, Deserialize)]
+pub struct VerificationRequirement {
+    pub min_amount: u64,
+    pub level: VerificationLevel,
+}
+
+/// Highest level required for a contract amount.
+/// Contracts without price could be of any amount, so they need the highest configured level.
+pub fn required_verification_level(requirements: &[VerificationRequirement], amount: Option<u64>) -> Option<VerificationLevel> {
+    requirements
+        .iter()
+        .filter(|requirement| amount.is_none_or(|amount| amount >= requirement.min_amount))
+        .map(|requirement| requirement.level)
+        .max()
+}
\ No newline at end of file
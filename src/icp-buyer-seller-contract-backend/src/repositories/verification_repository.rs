@@ -0,0 +1,46 @@
+use std::cell::RefCell;
+
+use candid::Principal;
+use crate::repositories::Verification;
+use super::{init_verifications, VerificationMemory};
+
+
+pub trait VerificationRepository {
+    fn get_verification(&self, principal: &Principal) -> Option<Verification>;
+    fn set_verification(&self, principal: Principal, verification: Verification);
+    fn remove_verification(&self, principal: &Principal) -> Option<Verification>;
+}
+
+pub struct VerificationRepositoryImpl;
+
+impl VerificationRepository for VerificationRepositoryImpl {
+    fn get_verification(&self, principal: &Principal) -> Option<Verification> {
+        STATE.with_borrow(|verifications| verifications.get(principal))
+    }
+
+    fn set_verification(&self, principal: Principal, verification: Verification) {
+        STATE.with_borrow_mut(|verifications| {
+            verifications.insert(principal, verification);
+        });
+    }
+
+    fn remove_verification(&self, principal: &Principal) -> Option<Verification> {
+        STATE.with_borrow_mut(|verifications| verifications.remove(principal))
+    }
+}
+
+impl VerificationRepositoryImpl {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for VerificationRepositoryImpl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+thread_local! {
+    static STATE: RefCell<VerificationMemory> = RefCell::new(init_verifications());
+}
\ No newline at end of file
@@ -1,13 +1,17 @@
-use crate::repositories::{ApiError, UserRepository, UserRepositoryImpl};
+use crate::repositories::{ApiError, Contract, Permission, UserRepository, UserRepositoryImpl};
 use candid::Principal;
 
 pub trait AccessControlService {
     fn assert_principal_not_anonymous(&self, calling_principal: &Principal)
         -> Result<(), ApiError>;
 
-    fn assert_principal_is_admin(&self, calling_principal: &Principal) -> Result<(), ApiError>;
+    fn assert_permission(&self, calling_principal: &Principal, permission: Permission) -> Result<(), ApiError>;
 
-    fn assert_principal_is_frontend(&self, calling_principal: &Principal) -> Result<(), ApiError>;
+    fn has_permission(&self, calling_principal: &Principal, permission: Permission) -> bool;
+
+    fn assert_principal_is_controller(&self, calling_principal: &Principal) -> Result<(), ApiError>;
+
+    fn assert_can_read_contract(&self, calling_principal: &Principal, contract: &Contract) -> Result<(), ApiError>;
 }
 
 pub struct AccessControlServiceImpl<T: UserRepository> {
@@ -32,20 +36,29 @@ impl<T: UserRepository> AccessControlService for AccessControlServiceImpl<T> {
         Ok(())
     }
 
-    fn assert_principal_is_admin(&self, calling_principal: &Principal) -> Result<(), ApiError> {
-        let user = self
-            .user_repository
+    /// Check that one of the caller's roles grants the permission
+    fn assert_permission(&self, calling_principal: &Principal, permission: Permission) -> Result<(), ApiError> {
+        if !self.has_permission(calling_principal, permission) {
+            return Err(ApiError::permission_denied(&format!(
+                "Principal {} needs permission {} to call this endpoint",
+                calling_principal.to_text(),
+                permission.name()
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn has_permission(&self, calling_principal: &Principal, permission: Permission) -> bool {
+        self.user_repository
             .get_user_by_principal(calling_principal)
-            .ok_or_else(|| {
-                ApiError::not_found(&format!(
-                    "Principal {} must be admin to call this endpoint",
-                    calling_principal.to_text()
-                ))
-            })?;
-
-        if !user.is_admin() {
+            .is_some_and(|user| user.has_permission(permission))
+    }
+
+    fn assert_principal_is_controller(&self, calling_principal: &Principal) -> Result<(), ApiError> {
+        if !ic_cdk::api::is_controller(calling_principal) {
             return Err(ApiError::permission_denied(&format!(
-                "Principal {} must be an admin to call this endpoint",
+                "Principal {} must be a controller of the canister to call this endpoint",
                 calling_principal.to_text()
             )));
         }
@@ -53,20 +66,15 @@ impl<T: UserRepository> AccessControlService for AccessControlServiceImpl<T> {
         Ok(())
     }
 
-    fn assert_principal_is_frontend(&self, calling_principal: &Principal) -> Result<(), ApiError> {
-        let user = self
-            .user_repository
-            .get_user_by_principal(calling_principal)
-            .ok_or_else(|| {
-                ApiError::not_found(&format!(
-                    "{} not authorized. Only frontend server principals can call this endpoint.",
-                    calling_principal.to_text()
-                ))
-            })?;
-
-        if !user.is_frontend_server() {
+    /// Contracts can be read by their signatories and principals allowed to read all contracts
+    fn assert_can_read_contract(&self, calling_principal: &Principal, contract: &Contract) -> Result<(), ApiError> {
+        if contract.is_signatory(calling_principal) {
+            return Ok(());
+        }
+
+        if !self.has_permission(calling_principal, Permission::ContractsReadAll) {
             return Err(ApiError::permission_denied(&format!(
-                "{} not authorized. Only frontend server principals can call this endpoint.",
+                "{} not authorized to read this contract",
                 calling_principal.to_text()
             )));
         }
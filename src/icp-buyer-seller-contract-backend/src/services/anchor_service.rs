@@ -0,0 +1,334 @@
+use std::cell::{Cell, RefCell};
+use alloy::primitives::{keccak256, Address, B256};
+use ic_stable_structures::Storable;
+use crate::repositories::{ApiError, AuditAction, AuditEntry, AuditRepository, AuditRepositoryImpl, ConfigRepository, ConfigRepositoryImpl, ContractAnchor, ContractRepository, ContractRepositoryImpl, Uuid};
+use crate::system_api::{certify_data, set_certified_contract};
+
+use super::{WalletService, WalletServiceImpl};
+
+/// Maximum number of contracts anchored with a single Merkle root
+const MAX_ANCHOR_BATCH_SIZE: usize = 256;
+/// Time after which a root whose transaction has not been mined is given up and its contracts anchored again
+const ANCHOR_CONFIRMATION_TIMEOUT: u64 = 24 * 60 * 60 * 1_000_000_000;
+
+/// A contract of a Merkle root written to the registry
+struct AnchorLeaf {
+    contract_id: Uuid,
+    version: u32,
+    content_hash: String,
+    leaf: B256,
+    proof: Vec<B256>,
+}
+
+/// A Merkle root written to the registry whose transaction has not been confirmed yet
+struct SubmittedRoot {
+    root: B256,
+    tx_hash: B256,
+    submitted_at: u64,
+    leaves: Vec<AnchorLeaf>,
+}
+
+thread_local! {
+    // anchoring awaits the EVM calls, prevent a second run from anchoring the same batch meanwhile
+    static ANCHORING: Cell<bool> = const { Cell::new(false) };
+    // the contracts of a root submitted before an upgrade stay pending and are anchored again
+    static SUBMITTED: RefCell<Option<SubmittedRoot>> = const { RefCell::new(None) };
+}
+
+/// Marks an anchoring run as in progress until it is dropped,
+/// including when the run returns early or its future is dropped after a trap
+struct AnchoringGuard;
+
+impl AnchoringGuard {
+    fn acquire() -> Result<Self, ApiError> {
+        if ANCHORING.replace(true) {
+            return Err(ApiError::conflict("Contracts are already being anchored"));
+        }
+        Ok(Self)
+    }
+}
+
+impl Drop for AnchoringGuard {
+    fn drop(&mut self) {
+        ANCHORING.set(false);
+    }
+}
+
+pub trait AnchorService {
+    async fn anchor_pending_contracts(&self) -> Result<u32, ApiError>;
+}
+
+pub struct AnchorServiceImpl<T: ContractRepository, U: WalletService, V: ConfigRepository, W: AuditRepository> {
+    contract_repository: T,
+    wallet_service: U,
+    config_repository: V,
+    audit_repository: W,
+}
+
+impl Default for AnchorServiceImpl<ContractRepositoryImpl, WalletServiceImpl, ConfigRepositoryImpl, AuditRepositoryImpl> {
+    fn default() -> Self {
+        Self::new(ContractRepositoryImpl::default(), WalletServiceImpl::default(), ConfigRepositoryImpl::default(), AuditRepositoryImpl::default())
+    }
+}
+
+impl<T: ContractRepository, U: WalletService, V: ConfigRepository, W: AuditRepository> AnchorServiceImpl<T, U, V, W> {
+    pub fn new(contract_repository: T, wallet_service: U, config_repository: V, audit_repository: W) -> Self {
+        Self { contract_repository, wallet_service, config_repository, audit_repository }
+    }
+
+    pub fn with_wallet(mut self, wallet: U) -> Self {
+        self.wallet_service = wallet;
+        self
+    }
+
+    /// Check the transaction of the root submitted by the previous run.
+    /// Returns the root if it was mined successfully, keeps it for the next run while it is not mined yet.
+    async fn confirm_submitted_root(&self) -> Result<Option<SubmittedRoot>, ApiError> {
+        let Some(submitted) = SUBMITTED.take() else {
+            return Ok(None);
+        };
+
+        let status = match self.wallet_service.get_transaction_status(submitted.tx_hash).await {
+            Ok(status) => status,
+            Err(e) => {
+                self.audit_failure(None, format!("checking transaction {} failed: {}", submitted.tx_hash, e.message()));
+                SUBMITTED.set(Some(submitted));
+                return Err(e);
+            }
+        };
+
+        match status {
+            Some(true) => Ok(Some(submitted)),
+            Some(false) => {
+                self.audit_abandoned_root(&submitted, "reverted");
+                Ok(None)
+            }
+            None if ic_cdk::api::time() < submitted.submitted_at.saturating_add(ANCHOR_CONFIRMATION_TIMEOUT) => {
+                SUBMITTED.set(Some(submitted));
+                Err(ApiError::conflict("The previous anchoring transaction is not mined yet"))
+            }
+            None => {
+                self.audit_abandoned_root(&submitted, "was not mined in time");
+                Ok(None)
+            }
+        }
+    }
+
+    /// Record a failed anchoring step in the audit log, with the canister as actor
+    fn audit_failure(&self, contract_id: Option<Uuid>, reason: String) {
+        let mut entry = AuditEntry::new(ic_cdk::api::id(), AuditAction::AnchoringFailed)
+            .with_change(None, Some(reason));
+        if let Some(contract_id) = contract_id {
+            entry = entry.with_contract(contract_id);
+        }
+        self.audit_repository.append_entry(entry);
+    }
+
+    /// Record on each contract of a root given up that it is anchored again
+    fn audit_abandoned_root(&self, submitted: &SubmittedRoot, reason: &str) {
+        for leaf in submitted.leaves.iter() {
+            self.audit_failure(Some(leaf.contract_id), format!(
+                "transaction {} of root {} {}, anchoring version {} again",
+                submitted.tx_hash, submitted.root, reason, leaf.version
+            ));
+        }
+    }
+
+    /// Store the anchor and proof of every contract of a confirmed root,
+    /// update their certified hashes and record the anchoring in the audit log
+    fn store_anchors(&self, confirmed: &SubmittedRoot) -> u32 {
+        let anchored_at = ic_cdk::api::time();
+        for leaf in confirmed.leaves.iter() {
+            self.contract_repository.update_contract_anchor(leaf.contract_id, ContractAnchor {
+                version: leaf.version,
+                content_hash: leaf.content_hash.clone(),
+                leaf: leaf.leaf.to_string(),
+                merkle_root: confirmed.root.to_string(),
+                merkle_proof: leaf.proof.iter().map(|hash| hash.to_string()).collect(),
+                tx_hash: confirmed.tx_hash.to_string(),
+                anchored_at,
+            });
+
+            if let Some(contract) = self.contract_repository.get_contract(leaf.contract_id) {
+                let certified = contract.certified();
+                set_certified_contract(&leaf.contract_id.to_string(), certified.hash, certified.is_signed);
+                self.contract_repository.set_certified_hash(leaf.contract_id, certified);
+            }
+
+            let entry = AuditEntry::new(ic_cdk::api::id(), AuditAction::ContractAnchored)
+                .with_contract(leaf.contract_id)
+                .with_change(None, Some(format!(
+                    "version {} anchored in root {}, transaction {}",
+                    leaf.version, confirmed.root, confirmed.tx_hash
+                )));
+            self.audit_repository.append_entry(entry);
+        }
+        certify_data();
+
+        confirmed.leaves.len() as u32
+    }
+}
+
+impl<T: ContractRepository, U: WalletService, V: ConfigRepository, W: AuditRepository> AnchorService for AnchorServiceImpl<T, U, V, W> {
+    /// Confirm the Merkle root submitted by the previous run and store the anchor with its proof on each of its contracts,
+    /// then write the hashes of the signed contracts that are still not anchored to the registry contract as a new root.
+    /// Contracts are only marked anchored once the transaction of their root is mined successfully.
+    /// Returns the number of contracts whose anchoring was confirmed.
+    async fn anchor_pending_contracts(&self) -> Result<u32, ApiError> {
+        let Some(anchor_config) = self.config_repository.get_config().anchor else {
+            return Ok(0);
+        };
+        let registry = anchor_config.registry_address.parse::<Address>()
+            .map_err(|e| ApiError::invalid_argument(&format!("Invalid registry address: {}", e)))?;
+
+        let _guard = AnchoringGuard::acquire()?;
+
+        let anchored = match self.confirm_submitted_root().await? {
+            Some(confirmed) => self.store_anchors(&confirmed),
+            None => 0,
+        };
+
+        let mut batch = vec![];
+        for contract_id in self.contract_repository.list_pending_anchors(MAX_ANCHOR_BATCH_SIZE) {
+            if let Some(contract) = self.contract_repository.get_contract(contract_id) {
+                let leaf = anchor_leaf(contract_id, &contract.content_hash)?;
+                batch.push((contract_id, contract.version, contract.content_hash, leaf));
+            }
+        }
+        if batch.is_empty() {
+            return Ok(anchored);
+        }
+
+        let leaves: Vec<B256> = batch.iter().map(|(_, _, _, leaf)| *leaf).collect();
+        let (root, proofs) = merkle_tree(&leaves);
+
+        let tx_hash = self.wallet_service.anchor_root(registry, root, batch.len() as u64)
+            .await
+            .inspect_err(|e| self.audit_failure(None, format!("submitting root {} of {} contracts failed: {}", root, batch.len(), e.message())))?;
+        let leaves = batch
+            .into_iter()
+            .zip(proofs)
+            .map(|((contract_id, version, content_hash, leaf), proof)| AnchorLeaf { contract_id, version, content_hash, leaf, proof })
+            .collect();
+        SUBMITTED.set(Some(SubmittedRoot { root, tx_hash, submitted_at: ic_cdk::api::time(), leaves }));
+
+        Ok(anchored)
+    }
+}
+
+/// Leaf of a contract in the Merkle tree: keccak256 of the 16 uuid bytes followed by the 32 content hash bytes
+fn anchor_leaf(contract_id: Uuid, content_hash: &str) -> Result<B256, ApiError> {
+    let content_hash = hex::decode(content_hash)
+        .map_err(|e| ApiError::internal(&format!("Invalid content hash: {}", e)))?;
+
+    let mut preimage = contract_id.to_bytes().to_vec();
+    preimage.extend_from_slice(&content_hash);
+    Ok(keccak256(preimage))
+}
+
+/// Hash two nodes in sorted order, as expected by OpenZeppelin's MerkleProof
+fn hash_pair(a: B256, b: B256) -> B256 {
+    let (left, right) = if a <= b { (a, b) } else { (b, a) };
+    keccak256([left.as_slice(), right.as_slice()].concat())
+}
+
+/// Compute the Merkle root of the leaves and the proof of every leaf.
+/// A node without sibling is promoted to the next level unchanged.
+fn merkle_tree(leaves: &[B256]) -> (B256, Vec<Vec<B256>>) {
+    let mut proofs = vec![vec![]; leaves.len()];
+    let mut positions: Vec<usize> = (0..leaves.len()).collect();
+    let mut level = leaves.to_vec();
+
+    while level.len() > 1 {
+        for (proof, position) in proofs.iter_mut().zip(positions.iter_mut()) {
+            if let Some(sibling) = level.get(*position ^ 1) {
+                proof.push(*sibling);
+            }
+            *position /= 2;
+        }
+
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [a, b] => hash_pair(*a, *b),
+                [a] => *a,
+                _ => unreachable!(),
+            })
+            .collect();
+    }
+
+    (level[0], proofs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// MerkleProof.processProof of OpenZeppelin: fold the proof into the leaf, hashing each pair in sorted order
+    fn process_proof(leaf: B256, proof: &[B256]) -> B256 {
+        proof.iter().fold(leaf, |hash, sibling| {
+            if hash < *sibling {
+                keccak256([hash.as_slice(), sibling.as_slice()].concat())
+            } else {
+                keccak256([sibling.as_slice(), hash.as_slice()].concat())
+            }
+        })
+    }
+
+    fn leaves(count: u8) -> Vec<B256> {
+        (0..count).map(|i| keccak256([i])).collect()
+    }
+
+    #[test]
+    fn hash_pair_sorts_the_nodes() {
+        let [a, b] = [keccak256(b"a"), keccak256(b"b")];
+        let (left, right) = if a < b { (a, b) } else { (b, a) };
+        let expected = keccak256([left.as_slice(), right.as_slice()].concat());
+
+        assert_eq!(hash_pair(a, b), expected);
+        assert_eq!(hash_pair(b, a), expected);
+    }
+
+    #[test]
+    fn single_leaf_is_the_root() {
+        let leaves = leaves(1);
+
+        let (root, proofs) = merkle_tree(&leaves);
+
+        assert_eq!(root, leaves[0]);
+        assert_eq!(proofs, vec![Vec::<B256>::new()]);
+    }
+
+    #[test]
+    fn three_leaves_promote_the_last_one() {
+        let leaves = leaves(3);
+
+        let (root, proofs) = merkle_tree(&leaves);
+
+        assert_eq!(root, hash_pair(hash_pair(leaves[0], leaves[1]), leaves[2]));
+        assert_eq!(proofs[2], vec![hash_pair(leaves[0], leaves[1])]);
+    }
+
+    #[test]
+    fn proofs_verify_like_openzeppelin() {
+        for count in 1..=17 {
+            let leaves = leaves(count);
+
+            let (root, proofs) = merkle_tree(&leaves);
+
+            for (leaf, proof) in leaves.iter().zip(proofs.iter()) {
+                assert_eq!(process_proof(*leaf, proof), root, "{} leaves", count);
+            }
+        }
+    }
+
+    #[test]
+    fn proof_does_not_verify_another_leaf() {
+        let leaves = leaves(5);
+
+        let (root, proofs) = merkle_tree(&leaves);
+
+        assert_ne!(process_proof(leaves[1], &proofs[0]), root);
+        assert_ne!(process_proof(keccak256(b"other"), &proofs[4]), root);
+    }
+}
\ No newline at end of file
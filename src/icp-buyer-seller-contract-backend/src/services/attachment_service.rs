@@ -0,0 +1,175 @@
+use candid::Principal;
+use sha2::{Digest, Sha256};
+use crate::repositories::{ApiError, Attachment, AttachmentRepository, AttachmentRepositoryImpl, AttachmentStatus, AuditAction, AuditEntry, AuditRepository, AuditRepositoryImpl, ContractRepository, ContractRepositoryImpl, Uuid, MAX_ATTACHMENTS_PER_CONTRACT};
+
+pub trait AttachmentService {
+    fn create_attachment(&self, contract_id: String, file_name: String, mime_type: String, size: u64, sha256: String, caller: Principal) -> Result<Uuid, ApiError>;
+    fn upload_attachment_chunk(&self, contract_id: String, attachment_id: String, index: u32, data: Vec<u8>, caller: Principal) -> Result<(), ApiError>;
+    fn finalize_attachment(&self, contract_id: String, attachment_id: String, caller: Principal) -> Result<(), ApiError>;
+    fn abort_attachment(&self, contract_id: String, attachment_id: String, caller: Principal) -> Result<(), ApiError>;
+    fn get_attachment(&self, contract_id: String, attachment_id: String) -> Result<Attachment, ApiError>;
+    fn list_attachments(&self, contract_id: String) -> Result<Vec<Attachment>, ApiError>;
+    fn get_attachment_chunk(&self, contract_id: String, attachment_id: String, index: u32) -> Result<Vec<u8>, ApiError>;
+}
+
+pub struct AttachmentServiceImpl<T: AttachmentRepository, U: ContractRepository, V: AuditRepository> {
+    attachment_repository: T,
+    contract_repository: U,
+    audit_repository: V,
+}
+
+impl Default for AttachmentServiceImpl<AttachmentRepositoryImpl, ContractRepositoryImpl, AuditRepositoryImpl> {
+    fn default() -> Self {
+        Self::new(AttachmentRepositoryImpl::default(), ContractRepositoryImpl::default(), AuditRepositoryImpl::default())
+    }
+}
+
+impl<T: AttachmentRepository, U: ContractRepository, V: AuditRepository> AttachmentServiceImpl<T, U, V> {
+    pub fn new(attachment_repository: T, contract_repository: U, audit_repository: V) -> Self {
+        Self { attachment_repository, contract_repository, audit_repository }
+    }
+
+    fn audit_attachment(&self, attachment: &Attachment, action: AuditAction, before: Option<AttachmentStatus>, after: Option<AttachmentStatus>) {
+        let summary = |status: AttachmentStatus| {
+            format!("attachment {} ({}, {} bytes, sha256 {}) {:?}", attachment.attachment_id, attachment.file_name, attachment.size, attachment.sha256, status)
+        };
+        let entry = AuditEntry::new(attachment.uploaded_by, action)
+            .with_contract(attachment.contract_id)
+            .with_change(before.map(summary), after.map(summary));
+        self.audit_repository.append_entry(entry);
+    }
+
+    /// Get a pending attachment the caller is uploading
+    fn get_pending_attachment(&self, contract_id: &str, attachment_id: &str, caller: &Principal) -> Result<Attachment, ApiError> {
+        let attachment = self.get_attachment(contract_id.to_string(), attachment_id.to_string())?;
+
+        if attachment.uploaded_by != *caller {
+            return Err(ApiError::permission_denied("Caller not authorized to upload this attachment"));
+        }
+
+        if attachment.is_complete() {
+            return Err(ApiError::conflict("Attachment has already been finalized"));
+        }
+
+        if attachment.is_expired(ic_cdk::api::time()) {
+            self.remove_attachment(&attachment);
+            return Err(ApiError::conflict("Attachment upload has expired"));
+        }
+
+        Ok(attachment)
+    }
+
+    fn remove_attachment(&self, attachment: &Attachment) {
+        self.attachment_repository.remove_attachment(attachment);
+        self.audit_attachment(attachment, AuditAction::AttachmentRemoved, Some(attachment.status), None);
+    }
+
+    /// Discard the uploads of a contract that expired before being finalized
+    fn remove_expired_attachments(&self, contract_id: Uuid) {
+        let now = ic_cdk::api::time();
+        for attachment in self.attachment_repository.list_attachments(contract_id) {
+            if attachment.is_expired(now) {
+                self.remove_attachment(&attachment);
+            }
+        }
+    }
+}
+
+impl<T: AttachmentRepository, U: ContractRepository, V: AuditRepository> AttachmentService for AttachmentServiceImpl<T, U, V> {
+    /// Start the upload of an attachment to a contract, only signatories may attach documents.
+    /// The content is uploaded with upload_attachment_chunk and verified by finalize_attachment.
+    fn create_attachment(&self, contract_id: String, file_name: String, mime_type: String, size: u64, sha256: String, caller: Principal) -> Result<Uuid, ApiError> {
+        let contract_id = Uuid::try_from(contract_id.as_str())?;
+        let contract = self.contract_repository.get_contract(contract_id)
+            .ok_or_else(|| ApiError::not_found("Contract not found"))?;
+
+        if !contract.is_signatory(&caller) {
+            return Err(ApiError::permission_denied("Caller not authorized to attach documents to this contract"));
+        }
+
+        self.remove_expired_attachments(contract_id);
+        if self.attachment_repository.list_attachments(contract_id).len() >= MAX_ATTACHMENTS_PER_CONTRACT {
+            return Err(ApiError::conflict(&format!(
+                "A contract can have at most {} attachments",
+                MAX_ATTACHMENTS_PER_CONTRACT
+            )));
+        }
+
+        let attachment = Attachment::new(contract_id, file_name, mime_type, size, sha256, caller)?;
+        let attachment_id = attachment.attachment_id;
+        self.audit_attachment(&attachment, AuditAction::AttachmentCreated, None, Some(AttachmentStatus::Pending));
+        self.attachment_repository.add_attachment(attachment);
+
+        Ok(attachment_id)
+    }
+
+    /// Store a chunk of a pending attachment, uploading a chunk again replaces it
+    fn upload_attachment_chunk(&self, contract_id: String, attachment_id: String, index: u32, data: Vec<u8>, caller: Principal) -> Result<(), ApiError> {
+        let attachment = self.get_pending_attachment(&contract_id, &attachment_id, &caller)?;
+
+        let expected_size = attachment.chunk_size(index).ok_or_else(|| {
+            ApiError::invalid_argument(&format!("Attachment has {} chunks", attachment.chunk_count))
+        })?;
+        if data.len() as u64 != expected_size {
+            return Err(ApiError::invalid_argument(&format!(
+                "Chunk {} must be {} bytes",
+                index, expected_size
+            )));
+        }
+
+        self.attachment_repository.put_chunk(attachment.attachment_id, index, data);
+        Ok(())
+    }
+
+    /// Check that every chunk has been uploaded and that the content matches the declared sha256
+    fn finalize_attachment(&self, contract_id: String, attachment_id: String, caller: Principal) -> Result<(), ApiError> {
+        let attachment = self.get_pending_attachment(&contract_id, &attachment_id, &caller)?;
+
+        let mut hasher = Sha256::new();
+        for index in 0..attachment.chunk_count {
+            let chunk = self.attachment_repository.get_chunk(attachment.attachment_id, index)
+                .ok_or_else(|| ApiError::conflict(&format!("Chunk {} has not been uploaded", index)))?;
+            hasher.update(&chunk);
+        }
+
+        if hex::encode(hasher.finalize()) != attachment.sha256 {
+            return Err(ApiError::conflict("Uploaded content does not match the declared sha256"));
+        }
+
+        self.attachment_repository.update_attachment_status(attachment.contract_id, attachment.attachment_id, AttachmentStatus::Complete);
+        self.audit_attachment(&attachment, AuditAction::AttachmentFinalized, Some(AttachmentStatus::Pending), Some(AttachmentStatus::Complete));
+        Ok(())
+    }
+
+    /// Cancel an upload that has not been finalized, removing the chunks uploaded so far
+    fn abort_attachment(&self, contract_id: String, attachment_id: String, caller: Principal) -> Result<(), ApiError> {
+        let attachment = self.get_pending_attachment(&contract_id, &attachment_id, &caller)?;
+
+        self.remove_attachment(&attachment);
+        Ok(())
+    }
+
+    fn get_attachment(&self, contract_id: String, attachment_id: String) -> Result<Attachment, ApiError> {
+        let contract_id = Uuid::try_from(contract_id.as_str())?;
+        let attachment_id = Uuid::try_from(attachment_id.as_str())?;
+        self.attachment_repository.get_attachment(contract_id, attachment_id)
+            .ok_or_else(|| ApiError::not_found("Attachment not found"))
+    }
+
+    fn list_attachments(&self, contract_id: String) -> Result<Vec<Attachment>, ApiError> {
+        let contract_id = Uuid::try_from(contract_id.as_str())?;
+        Ok(self.attachment_repository.list_attachments(contract_id))
+    }
+
+    /// Download a chunk of a finalized attachment
+    fn get_attachment_chunk(&self, contract_id: String, attachment_id: String, index: u32) -> Result<Vec<u8>, ApiError> {
+        let attachment = self.get_attachment(contract_id, attachment_id)?;
+
+        if !attachment.is_complete() {
+            return Err(ApiError::conflict("Attachment upload has not been finalized"));
+        }
+
+        self.attachment_repository.get_chunk(attachment.attachment_id, index)
+            .ok_or_else(|| ApiError::not_found("Chunk not found"))
+    }
+}
\ No newline at end of file
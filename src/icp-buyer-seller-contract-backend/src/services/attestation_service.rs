@@ -0,0 +1,107 @@
+use alloy::primitives::{FixedBytes, U256};
+use alloy::sol_types::SolStruct;
+use ic_stable_structures::Storable;
+use crate::repositories::{ApiError, AttestationPublicKey, ContractAttestation, ContractRepository, ContractRepositoryImpl, Uuid};
+
+use super::{eip712, WalletService, WalletServiceImpl};
+
+/// Derivation path of the attestation key, kept apart from the treasury key used for payments and anchoring
+pub const ATTESTATION_DERIVATION_PATH: &[u8] = b"attestation";
+
+fn attestation_derivation_path() -> Vec<Vec<u8>> {
+    vec![ATTESTATION_DERIVATION_PATH.to_vec()]
+}
+
+pub trait AttestationService {
+    async fn attest_contract(&self, contract_id: String) -> Result<ContractAttestation, ApiError>;
+    async fn get_attestation_public_key(&self) -> Result<AttestationPublicKey, ApiError>;
+}
+
+pub struct AttestationServiceImpl<T: ContractRepository, U: WalletService> {
+    contract_repository: T,
+    wallet_service: U,
+}
+
+impl Default for AttestationServiceImpl<ContractRepositoryImpl, WalletServiceImpl> {
+    fn default() -> Self {
+        Self::new(ContractRepositoryImpl::default(), WalletServiceImpl::default())
+    }
+}
+
+impl<T: ContractRepository, U: WalletService> AttestationServiceImpl<T, U> {
+    pub fn new(contract_repository: T, wallet_service: U) -> Self {
+        Self { contract_repository, wallet_service }
+    }
+
+    pub fn with_wallet(mut self, wallet: U) -> Self {
+        self.wallet_service = wallet;
+        self
+    }
+}
+
+impl<T: ContractRepository, U: WalletService> AttestationService for AttestationServiceImpl<T, U> {
+    /// Sign the current state of a contract as EIP-712 typed data.
+    /// The attestation is signed once per (version, status) and returned as stored afterwards,
+    /// so its timestamp is the time that state was first attested.
+    async fn attest_contract(&self, contract_id: String) -> Result<ContractAttestation, ApiError> {
+        let contract_uuid = Uuid::try_from(contract_id.as_str())?;
+        let contract = self.contract_repository.get_contract(contract_uuid)
+            .ok_or_else(|| ApiError::not_found("Contract not found"))?;
+
+        let content_hash = hex::decode(&contract.content_hash)
+            .ok()
+            .and_then(|hash| FixedBytes::<32>::try_from(hash.as_slice()).ok())
+            .ok_or_else(|| ApiError::internal("Invalid content hash"))?;
+
+        let status = contract.status();
+        let chain_id = self.wallet_service.chain_id();
+        if let Some(attestation) = self.contract_repository.get_attestation(contract_uuid) {
+            if attestation.is_current(contract.version, status, chain_id) {
+                return Ok(attestation);
+            }
+        }
+
+        let timestamp = ic_cdk::api::time() / 1_000_000_000;
+
+        let typed_data = eip712::ContractAttestation {
+            contractId: FixedBytes::<16>::from_slice(&contract_uuid.to_bytes()),
+            contentHash: content_hash,
+            status: format!("{:?}", status),
+            version: contract.version,
+            price: U256::from(contract.terms.price.unwrap_or(0)),
+            currency: contract.terms.currency.clone().unwrap_or_default(),
+            timestamp,
+        };
+        let digest = typed_data.eip712_signing_hash(&eip712::domain(chain_id));
+
+        let signature = self.wallet_service.sign_hash(digest, attestation_derivation_path()).await?;
+        let signer = self.wallet_service.get_derived_address(attestation_derivation_path()).await?;
+
+        let attestation = ContractAttestation {
+            contract_id,
+            content_hash: contract.content_hash,
+            status,
+            version: contract.version,
+            price: contract.terms.price,
+            currency: contract.terms.currency,
+            timestamp,
+            chain_id,
+            digest: digest.to_string(),
+            signature: format!("0x{}", hex::encode(signature)),
+            signer,
+        };
+        self.contract_repository.set_attestation(contract_uuid, attestation.clone());
+
+        Ok(attestation)
+    }
+
+    async fn get_attestation_public_key(&self) -> Result<AttestationPublicKey, ApiError> {
+        let public_key = self.wallet_service.get_public_key(attestation_derivation_path()).await?;
+        let address = self.wallet_service.get_derived_address(attestation_derivation_path()).await?;
+
+        Ok(AttestationPublicKey {
+            public_key: format!("0x{}", hex::encode(public_key)),
+            address,
+        })
+    }
+}
\ No newline at end of file
@@ -0,0 +1,58 @@
+use candid::Principal;
+use crate::repositories::{ApiError, AuditEntry, AuditPage, AuditRepository, AuditRepositoryImpl, Uuid, DEFAULT_AUDIT_PAGE_SIZE, MAX_AUDIT_PAGE_SIZE};
+
+pub trait AuditService {
+    fn get_contract_audit_log(&self, contract_id: String, start: Option<u64>, limit: Option<u32>) -> Result<AuditPage, ApiError>;
+    fn get_principal_audit_log(&self, principal: Principal, start: Option<u64>, limit: Option<u32>) -> AuditPage;
+}
+
+pub struct AuditServiceImpl<T: AuditRepository> {
+    audit_repository: T,
+}
+
+impl Default for AuditServiceImpl<AuditRepositoryImpl> {
+    fn default() -> Self {
+        Self::new(AuditRepositoryImpl::default())
+    }
+}
+
+impl<T: AuditRepository> AuditServiceImpl<T> {
+    pub fn new(audit_repository: T) -> Self {
+        Self { audit_repository }
+    }
+}
+
+impl<T: AuditRepository> AuditService for AuditServiceImpl<T> {
+    /// Query the audit log entries of a contract, oldest first, starting at log index start
+    fn get_contract_audit_log(&self, contract_id: String, start: Option<u64>, limit: Option<u32>) -> Result<AuditPage, ApiError> {
+        let contract_id = Uuid::try_from(contract_id.as_str())?;
+        let limit = page_size(limit);
+        let entries = self.audit_repository.list_entries_by_contract(contract_id, start.unwrap_or(0), limit + 1);
+
+        Ok(to_page(entries, limit))
+    }
+
+    /// Query the audit log entries of a principal, oldest first, starting at log index start
+    fn get_principal_audit_log(&self, principal: Principal, start: Option<u64>, limit: Option<u32>) -> AuditPage {
+        let limit = page_size(limit);
+        let entries = self.audit_repository.list_entries_by_principal(principal, start.unwrap_or(0), limit + 1);
+
+        to_page(entries, limit)
+    }
+}
+
+fn page_size(limit: Option<u32>) -> usize {
+    limit.unwrap_or(DEFAULT_AUDIT_PAGE_SIZE).clamp(1, MAX_AUDIT_PAGE_SIZE) as usize
+}
+
+/// Build a page from up to limit + 1 entries, the extra entry tells whether there is a next page
+fn to_page(mut entries: Vec<AuditEntry>, limit: usize) -> AuditPage {
+    let next_index = if entries.len() > limit {
+        entries.truncate(limit);
+        entries.last().map(|entry| entry.index + 1)
+    } else {
+        None
+    };
+
+    AuditPage { entries, next_index }
+}
\ No newline at end of file
@@ -0,0 +1,70 @@
+use alloy::primitives::Address;
+use candid::Principal;
+use crate::repositories::{ApiError, AuditAction, AuditEntry, AuditRepository, AuditRepositoryImpl, Config, ConfigRepository, ConfigRepositoryImpl, MAX_SIWE_SESSION_EXPIRES_IN};
+
+pub trait ConfigService {
+    fn get_config(&self) -> Config;
+    fn update_config(&self, config: Config, caller: Principal) -> Result<(), ApiError>;
+}
+
+pub struct ConfigServiceImpl<T: ConfigRepository, U: AuditRepository> {
+    config_repository: T,
+    audit_repository: U,
+}
+
+impl Default for ConfigServiceImpl<ConfigRepositoryImpl, AuditRepositoryImpl> {
+    fn default() -> Self {
+        Self::new(ConfigRepositoryImpl::default(), AuditRepositoryImpl::default())
+    }
+}
+
+impl<T: ConfigRepository, U: AuditRepository> ConfigServiceImpl<T, U> {
+    pub fn new(config_repository: T, audit_repository: U) -> Self {
+        Self { config_repository, audit_repository }
+    }
+}
+
+impl<T: ConfigRepository, U: AuditRepository> ConfigService for ConfigServiceImpl<T, U> {
+    fn get_config(&self) -> Config {
+        self.config_repository.get_config()
+    }
+
+    fn update_config(&self, config: Config, caller: Principal) -> Result<(), ApiError> {
+        if let Some(anchor) = &config.anchor {
+            anchor.registry_address.parse::<Address>()
+                .map_err(|e| ApiError::invalid_argument(&format!("Invalid registry address: {}", e)))?;
+        }
+
+        if let Some(siwe) = &config.siwe {
+            if siwe.domain.is_empty() || siwe.uri.is_empty() {
+                return Err(ApiError::invalid_argument("SIWE domain and uri must not be empty"));
+            }
+            if siwe.session_expires_in.is_some_and(|expires_in| expires_in == 0 || expires_in > MAX_SIWE_SESSION_EXPIRES_IN) {
+                return Err(ApiError::invalid_argument(&format!(
+                    "SIWE session lifetime must be between 1 and {} nanoseconds",
+                    MAX_SIWE_SESSION_EXPIRES_IN
+                )));
+            }
+        }
+
+        if let Some(requirements) = &config.verification_requirements {
+            for (i, requirement) in requirements.iter().enumerate() {
+                if requirements[..i].iter().any(|other| other.min_amount == requirement.min_amount) {
+                    return Err(ApiError::invalid_argument(&format!(
+                        "Verification requirement for amount {} is listed more than once",
+                        requirement.min_amount
+                    )));
+                }
+            }
+        }
+
+        let before = self.config_repository.get_config();
+        let after = format!("{:?}", config);
+        self.config_repository.update_config(config)?;
+
+        let entry = AuditEntry::new(caller, AuditAction::ConfigUpdated)
+            .with_change(Some(format!("{:?}", before)), Some(after));
+        self.audit_repository.append_entry(entry);
+        Ok(())
+    }
+}
\ No newline at end of file
@@ -1,54 +1,407 @@
-use alloy::primitives::Address;
+use alloy::primitives::{Address, FixedBytes, Signature};
+use alloy::sol_types::SolStruct;
 use candid::Principal;
-use crate::repositories::{ApiError, Contract, ContractRepository, ContractRepositoryImpl, Signer, Uuid};
+use crate::repositories::{ApiError, CertifiedContract, CertifiedSignatureStatus, AuditAction, AuditEntry, AuditRepository, AuditRepositoryImpl, BlockRepository, BlockRepositoryImpl, ConfigRepositoryImpl, Contract, ContractListItem, ContractPage, ContractRepository, ContractSummary, PublicSummaryConfig, ContractRepositoryImpl, ContractCursor, ContractEncryption, ContractSignature, ContractTerms, ContractSort, ContractVersion, DenylistRepositoryImpl, DenylistSubject, EvmSignature, ListContractsRequest, SchemaRepository, SchemaRepositoryImpl, SiweRepository, SiweRepositoryImpl, SignatureQuorum, Signatory, SignatoryId, SignatoryRole, TemplateReference, Uuid, VerificationRepositoryImpl, ICRC3Value, BTYPE_CONTRACT_AMENDED, BTYPE_CONTRACT_CREATED, BTYPE_CONTRACT_SIGNED, BTYPE_PAYMENT_ISSUED, BTYPE_SIGNATURE_REVOKED, DEFAULT_CONTRACTS_PAGE_SIZE, MAX_CONTRACTS_PAGE_SIZE, MAX_CONTRACT_JSON_SIZE, content_hash, validate_content_hash};
+use crate::system_api::{certify_contract, certify_data, certify_icrc3_tip, contract_witness, encode_hash_tree, set_certified_contract, signed_witness, vetkd_derive_key, vetkd_public_key};
+use ic_stable_structures::Storable;
+use serde_bytes::ByteBuf;
 
-use super::{WalletService, WalletServiceImpl};
+use super::{eip712, DenylistService, DenylistServiceImpl, VerificationService, VerificationServiceImpl, WalletService, WalletServiceImpl};
 
 pub trait ContractService {
-    fn create_contract(&self, contract_json: String, buyer: Principal, seller: Principal) -> Uuid;
-    fn sign_contract(&self, contract_id: String, caller: Principal) -> Result<(), ApiError>;
-    fn get_contract(&self, contract_id: String) -> Option<Contract>;
-    fn is_signed(&self, contract_id: String) -> Result<bool, ApiError>;
-    async fn issue_payment(&self, contract_id: String, caller: Principal, address: Address, amount: u64) -> Result<(), ApiError>;
+    fn create_contract(&self, contract_json: String, buyer: Principal, seller: Principal, caller: Principal) -> Result<Uuid, ApiError>;
+    fn create_multi_party_contract(&self, contract_json: String, signatories: Vec<(Principal, SignatoryRole)>, evm_signatories: Vec<(String, SignatoryRole)>, quorum: SignatureQuorum, caller: Principal) -> Result<Uuid, ApiError>;
+    fn create_contract_from_template(&self, contract_json: String, template: TemplateReference, buyer: Principal, seller: Principal, caller: Principal) -> Result<Uuid, ApiError>;
+    fn create_encrypted_contract(&self, ciphertext: String, content_hash: String, key_id: Vec<u8>, buyer: Principal, seller: Principal, caller: Principal) -> Result<Uuid, ApiError>;
+    fn amend_contract(&self, contract_id: String, contract_json: String, caller: Principal) -> Result<u32, ApiError>;
+    fn amend_encrypted_contract(&self, contract_id: String, ciphertext: String, content_hash: String, caller: Principal) -> Result<u32, ApiError>;
+    fn sign_contract(&self, contract_id: String, content_hash: String, caller: Principal, delegation_origin: Option<String>) -> Result<(), ApiError>;
+    fn sign_contract_with_evm(&self, contract_id: String, content_hash: String, evm_signature: EvmSignature, caller: Principal) -> Result<(), ApiError>;
+    fn revoke_signature(&self, contract_id: String, caller: Principal) -> Result<(), ApiError>;
+    fn get_contract(&self, contract_id: String) -> Result<Contract, ApiError>;
+    fn get_certified_contract(&self, contract_id: String) -> Result<CertifiedContract, ApiError>;
+    fn get_certified_signature_status(&self, contract_id: String) -> Result<CertifiedSignatureStatus, ApiError>;
+    fn certify_contracts(&self);
+    fn migrate_legacy_contracts(&self);
+    fn get_contract_summary(&self, contract_id: String, config: &PublicSummaryConfig) -> Result<ContractSummary, ApiError>;
+    fn get_contract_versions(&self, contract_id: String) -> Result<Vec<ContractVersion>, ApiError>;
+    fn list_contracts(&self, request: ListContractsRequest, caller: Principal) -> Result<ContractPage, ApiError>;
+    async fn get_contract_encryption_key(&self, contract_id: String, transport_public_key: Vec<u8>, caller: Principal) -> Result<Vec<u8>, ApiError>;
+    async fn get_encryption_public_key(&self) -> Result<Vec<u8>, ApiError>;
+    async fn issue_payment(&self, contract_id: String, seller: Principal, address: Address, amount: u64, caller: Principal) -> Result<(), ApiError>;
 }
 
-pub struct ContractServiceImpl<T: ContractRepository, U: WalletService> {
+/// vetKD context for contract encryption keys, separating them from keys derived for other purposes
+const CONTRACT_ENCRYPTION_CONTEXT: &[u8] = b"tradeonchain-contract-encryption";
+const MIN_KEY_ID_SIZE: usize = 16;
+const MAX_KEY_ID_SIZE: usize = 64;
+
+pub struct ContractServiceImpl<T: ContractRepository, U: WalletService, V: SchemaRepository, W: AuditRepository, X: BlockRepository, Y: SiweRepository, Z: VerificationService, A: DenylistService> {
     contract_repository: T,
     wallet_service: U,
+    schema_repository: V,
+    audit_repository: W,
+    block_repository: X,
+    siwe_repository: Y,
+    verification_service: Z,
+    denylist_service: A,
 }
 
-impl<T: ContractRepository, U: WalletService> ContractServiceImpl<T, U> {
-    pub fn new(contract_repository: T, wallet_service: U) -> Self {
-        Self { contract_repository, wallet_service}
+impl<T: ContractRepository, U: WalletService, V: SchemaRepository, W: AuditRepository, X: BlockRepository, Y: SiweRepository, Z: VerificationService, A: DenylistService> ContractServiceImpl<T, U, V, W, X, Y, Z, A> {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(contract_repository: T, wallet_service: U, schema_repository: V, audit_repository: W, block_repository: X, siwe_repository: Y, verification_service: Z, denylist_service: A) -> Self {
+        Self { contract_repository, wallet_service, schema_repository, audit_repository, block_repository, siwe_repository, verification_service, denylist_service }
     }
 
     pub fn with_wallet(mut self, wallet: U) -> Self {
         self.wallet_service = wallet;
         self
     }
+
+    /// Check the size of a contract json, that it parses and that it matches the active schema, if any,
+    /// and extract its key terms
+    fn parse_contract_json(&self, contract_json: &str) -> Result<ContractTerms, ApiError> {
+        if contract_json.len() > MAX_CONTRACT_JSON_SIZE {
+            return Err(ApiError::invalid_argument(&format!(
+                "Contract json exceeds the maximum size of {} bytes",
+                MAX_CONTRACT_JSON_SIZE
+            )));
+        }
+
+        let contract: serde_json::Value = serde_json::from_str(contract_json).map_err(|e| {
+            ApiError::invalid_argument(&format!("Contract json is not valid json: {}", e))
+        })?;
+
+        if let Some(schema) = self.schema_repository.get_latest_schema() {
+            schema.validate(&contract)?;
+        }
+
+        ContractTerms::from_json(&contract)
+    }
+
+    fn check_ciphertext(&self, ciphertext: &str, content_hash: &str) -> Result<(), ApiError> {
+        if ciphertext.len() > MAX_CONTRACT_JSON_SIZE {
+            return Err(ApiError::invalid_argument(&format!(
+                "Contract ciphertext exceeds the maximum size of {} bytes",
+                MAX_CONTRACT_JSON_SIZE
+            )));
+        }
+
+        validate_content_hash(content_hash)
+    }
+
+    /// Validate the contract json and signatories of a new plaintext contract
+    fn build_contract(&self, contract_json: String, signatories: Vec<(Principal, SignatoryRole)>, evm_signatories: Vec<(String, SignatoryRole)>, quorum: SignatureQuorum, caller: Principal) -> Result<Contract, ApiError> {
+        let signatories = self.build_signatories(signatories, evm_signatories, &quorum)?;
+
+        let terms = self.parse_contract_json(&contract_json)?;
+        let content_hash = content_hash(&contract_json)?;
+        self.check_signatories_verified(&signatories, &terms)?;
+
+        Ok(Contract::new(contract_json, content_hash, terms, signatories, quorum, caller))
+    }
+
+    /// Check for duplicate principals and addresses and that the quorum can be met by the signatories.
+    /// Ethereum addresses are stored checksummed, with the principal the address logged in as, if any.
+    fn build_signatories(&self, signatories: Vec<(Principal, SignatoryRole)>, evm_signatories: Vec<(String, SignatoryRole)>, quorum: &SignatureQuorum) -> Result<Vec<Signatory>, ApiError> {
+        if signatories.is_empty() && evm_signatories.is_empty() {
+            return Err(ApiError::invalid_argument("A contract needs at least one signatory"));
+        }
+
+        for (i, (principal, _)) in signatories.iter().enumerate() {
+            if signatories[..i].iter().any(|(other, _)| other == principal) {
+                return Err(ApiError::invalid_argument(&format!(
+                    "Principal {} is listed more than once as signatory",
+                    principal.to_text()
+                )));
+            }
+        }
+
+        let mut evm_addresses: Vec<(Address, SignatoryRole)> = vec![];
+        for (address, role) in evm_signatories {
+            let address = address.parse::<Address>()
+                .map_err(|e| ApiError::invalid_argument(&format!("Invalid Ethereum address {}: {}", address, e)))?;
+            if evm_addresses.iter().any(|(other, _)| other == &address) {
+                return Err(ApiError::invalid_argument(&format!(
+                    "Address {} is listed more than once as signatory",
+                    address
+                )));
+            }
+            evm_addresses.push((address, role));
+        }
+
+        let mut evm_signatories = vec![];
+        for (address, role) in evm_addresses {
+            let mut signatory = Signatory::new_evm(address.to_string(), role);
+            signatory.principal = self.siwe_repository.get_principal_by_address(address);
+            if let Some(principal) = signatory.principal.filter(|principal| signatories.iter().any(|(other, _)| other == principal)) {
+                return Err(ApiError::invalid_argument(&format!(
+                    "Address {} belongs to principal {} which is already listed as signatory",
+                    address,
+                    principal.to_text()
+                )));
+            }
+            evm_signatories.push(signatory);
+        }
+
+        let signatories: Vec<Signatory> = signatories
+            .into_iter()
+            .map(|(principal, role)| Signatory::new(principal, role))
+            .chain(evm_signatories)
+            .collect();
+        quorum.validate(&signatories)?;
+
+        Ok(signatories)
+    }
+
+    /// Check that every signatory known by principal holds the verification level required for the contract price.
+    /// Ethereum signatories that have not logged in yet are checked when they sign.
+    fn check_signatories_verified(&self, signatories: &[Signatory], terms: &ContractTerms) -> Result<(), ApiError> {
+        for principal in signatories.iter().filter_map(|signatory| signatory.principal) {
+            self.verification_service.assert_verified(Some(principal), terms.price)?;
+        }
+        Ok(())
+    }
+
+    /// Check that the current version of a contract can be signed by a signatory who has seen content_hash
+    /// and has not signed it yet
+    fn check_signable(&self, contract: &Contract, signatory: &Signatory, content_hash: &str) -> Result<(), ApiError> {
+        if signatory.has_signed_version(contract.version) {
+            return Err(ApiError::conflict("Signatory has already signed the current version of the contract"));
+        }
+
+        if !contract.matches_content_hash(content_hash) {
+            return Err(ApiError::conflict("Content hash does not match the current version of the contract"));
+        }
+
+        if contract.terms.signing_deadline().is_some_and(|deadline| ic_cdk::api::time() >= deadline) {
+            return Err(ApiError::conflict("Contract cannot be signed after its delivery date"));
+        }
+
+        Ok(())
+    }
+
+    /// Get a contract the caller may amend.
+    /// Once the quorum is met the signed version is in force and cannot be replaced by a single party.
+    fn get_amendable_contract(&self, contract_id: Uuid, caller: &Principal) -> Result<Contract, ApiError> {
+        let contract = self.contract_repository.get_contract(contract_id)
+            .ok_or_else(|| ApiError::not_found("Contract not found"))?;
+
+        if !contract.is_signatory(caller) {
+            return Err(ApiError::permission_denied("Caller not authorized to amend this contract"));
+        }
+
+        if contract.issued_payment() {
+            return Err(ApiError::conflict("Contract cannot be amended after payment has been issued"));
+        }
+
+        if contract.is_signed() {
+            return Err(ApiError::conflict("Contract cannot be amended once it is fully signed"));
+        }
+
+        Ok(contract)
+    }
+
+    /// Record a change of a contract in the audit log, with the state before and after the change,
+    /// and update its certified hash. detail is appended to the summary of the new state.
+    fn record_contract_change(&self, contract_id: Uuid, actor: Principal, action: AuditAction, before: Option<&Contract>, detail: Option<String>) {
+        let contract = self.contract_repository.get_contract(contract_id);
+        if let Some(contract) = &contract {
+            let certified = contract.certified();
+            certify_contract(&contract_id.to_string(), certified.hash, certified.is_signed);
+            self.contract_repository.set_certified_hash(contract_id, certified);
+        }
+
+        let after = contract.map(|contract| {
+            let summary = audit_summary(&contract);
+            match &detail {
+                Some(detail) => format!("{}, {}", summary, detail),
+                None => summary,
+            }
+        });
+
+        let entry = AuditEntry::new(actor, action)
+            .with_contract(contract_id)
+            .with_change(before.map(audit_summary), after);
+        self.audit_repository.append_entry(entry);
+    }
+
+    /// Record a contract event in the ICRC-3 block log and certify the new tip.
+    /// The tx contains the state of the contract after the event and any extra fields.
+    fn append_contract_block(&self, contract_id: Uuid, actor: Principal, btype: &str, extra: Vec<(String, ICRC3Value)>) {
+        let Some(contract) = self.contract_repository.get_contract(contract_id) else {
+            return;
+        };
+
+        let mut tx = vec![
+            ("contract_id".to_string(), ICRC3Value::from(contract_id.to_string())),
+            ("actor".to_string(), ICRC3Value::from(actor)),
+            ("version".to_string(), ICRC3Value::from(contract.version as u64)),
+            ("content_hash".to_string(), ICRC3Value::from(contract.content_hash.clone())),
+            ("status".to_string(), ICRC3Value::from(format!("{:?}", contract.status()))),
+        ];
+        tx.extend(extra);
+
+        let (index, hash) = self.block_repository.append_block(btype, tx);
+        certify_icrc3_tip(index, hash);
+    }
 }
 
-impl Default for ContractServiceImpl<ContractRepositoryImpl, WalletServiceImpl> {
+fn audit_summary(contract: &Contract) -> String {
+    format!(
+        "status {:?}, version {}, content hash {}",
+        contract.status(),
+        contract.version,
+        contract.content_hash
+    )
+}
+
+impl Default for ContractServiceImpl<ContractRepositoryImpl, WalletServiceImpl, SchemaRepositoryImpl, AuditRepositoryImpl, BlockRepositoryImpl, SiweRepositoryImpl, VerificationServiceImpl<VerificationRepositoryImpl, ConfigRepositoryImpl, AuditRepositoryImpl>, DenylistServiceImpl<DenylistRepositoryImpl, AuditRepositoryImpl>> {
     fn default() -> Self {
-        Self::new(ContractRepositoryImpl::default(), WalletServiceImpl::default())
+        Self::new(
+            ContractRepositoryImpl::default(),
+            WalletServiceImpl::default(),
+            SchemaRepositoryImpl::default(),
+            AuditRepositoryImpl::default(),
+            BlockRepositoryImpl::default(),
+            SiweRepositoryImpl::default(),
+            VerificationServiceImpl::default(),
+            DenylistServiceImpl::default(),
+        )
     }
 }
 
-impl<T: ContractRepository, U: WalletService> ContractService for ContractServiceImpl<T, U> {
-    /// Create a new unsigned contract in storage
-    fn create_contract(&self, contract_json: String, buyer: Principal, seller: Principal) -> Uuid {
-        self.contract_repository.create_contract(contract_json, buyer, seller)
+impl<T: ContractRepository, U: WalletService, V: SchemaRepository, W: AuditRepository, X: BlockRepository, Y: SiweRepository, Z: VerificationService, A: DenylistService> ContractService for ContractServiceImpl<T, U, V, W, X, Y, Z, A> {
+    /// Create a new unsigned two-party contract in storage, requiring both signatures.
+    /// Buyer and seller must differ, see build_signatories.
+    fn create_contract(&self, contract_json: String, buyer: Principal, seller: Principal, caller: Principal) -> Result<Uuid, ApiError> {
+        self.create_multi_party_contract(
+            contract_json,
+            vec![(buyer, SignatoryRole::Buyer), (seller, SignatoryRole::Seller)],
+            vec![],
+            SignatureQuorum::All,
+            caller,
+        )
+    }
+
+    /// Create a new unsigned contract with an arbitrary list of signatories,
+    /// identified by principal or by the Ethereum address they sign with
+    fn create_multi_party_contract(&self, contract_json: String, signatories: Vec<(Principal, SignatoryRole)>, evm_signatories: Vec<(String, SignatoryRole)>, quorum: SignatureQuorum, caller: Principal) -> Result<Uuid, ApiError> {
+        let contract = self.build_contract(contract_json, signatories, evm_signatories, quorum, caller)?;
+        let contract_id = self.contract_repository.create_contract(contract, caller);
+        self.record_contract_change(contract_id, caller, AuditAction::ContractCreated, None, None);
+        self.append_contract_block(contract_id, caller, BTYPE_CONTRACT_CREATED, vec![]);
+
+        Ok(contract_id)
+    }
+
+    /// Create a new unsigned two-party contract from a rendered template,
+    /// recording the template version it was rendered from
+    fn create_contract_from_template(&self, contract_json: String, template: TemplateReference, buyer: Principal, seller: Principal, caller: Principal) -> Result<Uuid, ApiError> {
+        let detail = format!("template {} version {}", template.template_id, template.version);
+        let contract = self.build_contract(
+            contract_json,
+            vec![(buyer, SignatoryRole::Buyer), (seller, SignatoryRole::Seller)],
+            vec![],
+            SignatureQuorum::All,
+            caller,
+        )?
+        .with_template(template);
+        let contract_id = self.contract_repository.create_contract(contract, caller);
+        self.record_contract_change(contract_id, caller, AuditAction::ContractCreated, None, Some(detail));
+        self.append_contract_block(contract_id, caller, BTYPE_CONTRACT_CREATED, vec![]);
+
+        Ok(contract_id)
+    }
+
+    /// Create a new unsigned two-party contract whose json is encrypted client side with the contract's vetKey.
+    /// The canister cannot read the json, so it is not validated against the schema and no terms are extracted;
+    /// content_hash is the hash of the plaintext json, computed by the client.
+    fn create_encrypted_contract(&self, ciphertext: String, content_hash: String, key_id: Vec<u8>, buyer: Principal, seller: Principal, caller: Principal) -> Result<Uuid, ApiError> {
+        if key_id.len() < MIN_KEY_ID_SIZE || key_id.len() > MAX_KEY_ID_SIZE {
+            return Err(ApiError::invalid_argument(&format!(
+                "Key id must be between {} and {} bytes",
+                MIN_KEY_ID_SIZE, MAX_KEY_ID_SIZE
+            )));
+        }
+        self.check_ciphertext(&ciphertext, &content_hash)?;
+
+        let quorum = SignatureQuorum::All;
+        let signatories = self.build_signatories(
+            vec![(buyer, SignatoryRole::Buyer), (seller, SignatoryRole::Seller)],
+            vec![],
+            &quorum,
+        )?;
+        self.check_signatories_verified(&signatories, &ContractTerms::default())?;
+
+        let encryption = ContractEncryption { key_id, created_by: caller };
+        let contract = Contract::new(ciphertext, content_hash.to_lowercase(), ContractTerms::default(), signatories, quorum, caller)
+            .with_encryption(encryption);
+        let contract_id = self.contract_repository.create_contract(contract, caller);
+        self.record_contract_change(contract_id, caller, AuditAction::ContractCreated, None, Some("encrypted".to_string()));
+        self.append_contract_block(contract_id, caller, BTYPE_CONTRACT_CREATED, vec![]);
+
+        Ok(contract_id)
     }
 
-    /// Sign a contract
-    fn sign_contract(&self, contract_id: String, caller: Principal) -> Result<(), ApiError> {
+    /// Propose a new version of the contract json.
+    /// Every signatory has to sign the amended contract again.
+    fn amend_contract(&self, contract_id: String, contract_json: String, caller: Principal) -> Result<u32, ApiError> {
+        let contract_id = Uuid::try_from(contract_id.as_str())?;
+        let contract = self.get_amendable_contract(contract_id, &caller)?;
+
+        if contract.is_encrypted() {
+            return Err(ApiError::invalid_argument("Encrypted contracts must be amended with amend_encrypted_contract"));
+        }
+
+        let terms = self.parse_contract_json(&contract_json)?;
+        let content_hash = content_hash(&contract_json)?;
+
+        let version = self.contract_repository.amend_contract(contract_id, contract_json, content_hash, terms, caller)
+            .ok_or_else(|| ApiError::not_found("Contract not found"))?;
+        self.record_contract_change(contract_id, caller, AuditAction::ContractAmended, Some(&contract), None);
+        self.append_contract_block(contract_id, caller, BTYPE_CONTRACT_AMENDED, vec![]);
+
+        Ok(version)
+    }
+
+    /// Propose a new version of an encrypted contract, encrypted with the same vetKey
+    fn amend_encrypted_contract(&self, contract_id: String, ciphertext: String, content_hash: String, caller: Principal) -> Result<u32, ApiError> {
+        let contract_id = Uuid::try_from(contract_id.as_str())?;
+        let contract = self.get_amendable_contract(contract_id, &caller)?;
+
+        if !contract.is_encrypted() {
+            return Err(ApiError::invalid_argument("Contract is not encrypted"));
+        }
+        self.check_ciphertext(&ciphertext, &content_hash)?;
+
+        let version = self.contract_repository.amend_contract(contract_id, ciphertext, content_hash.to_lowercase(), ContractTerms::default(), caller)
+            .ok_or_else(|| ApiError::not_found("Contract not found"))?;
+        self.record_contract_change(contract_id, caller, AuditAction::ContractAmended, Some(&contract), None);
+        self.append_contract_block(contract_id, caller, BTYPE_CONTRACT_AMENDED, vec![]);
+
+        Ok(version)
+    }
+
+    /// Sign the current version of a contract.
+    /// The content hash must match the version the caller has seen, otherwise the signature is rejected.
+    fn sign_contract(&self, contract_id: String, content_hash: String, caller: Principal, delegation_origin: Option<String>) -> Result<(), ApiError> {
         let contract_id = Uuid::try_from(contract_id.as_str())?;
         if let Some(contract) = self.contract_repository.get_contract(contract_id) {
-            if caller == contract.signatories.buyer.0 {
-                self.contract_repository.update_contract_signature(contract_id, Signer::Buyer);
-                Ok(())
-            } else if caller == contract.signatories.seller.0 {
-                self.contract_repository.update_contract_signature(contract_id, Signer::Seller);
+            if let Some(signatory) = contract.signatory(&caller) {
+                self.check_signable(&contract, signatory, &content_hash)?;
+                // a signatory listed by Ethereum address can sign here once it linked a principal through SIWE
+                let mut subjects = vec![DenylistSubject::Principal(caller)];
+                subjects.extend(signatory.evm_address.clone().map(DenylistSubject::EvmAddress));
+                self.denylist_service.screen(&subjects, caller, Some(contract_id), "Signing")?;
+                self.verification_service.assert_verified(Some(caller), contract.terms.price)?;
+
+                let signature = ContractSignature::new(&contract, delegation_origin);
+                let detail = signature.delegation_origin.as_ref()
+                    .map(|origin| format!("signed via delegation from {}", origin));
+                self.contract_repository.update_contract_signature(contract_id, SignatoryId::Principal(caller), Some(signature), caller);
+                self.record_contract_change(contract_id, caller, AuditAction::ContractSigned, Some(&contract), detail);
+                self.append_contract_block(contract_id, caller, BTYPE_CONTRACT_SIGNED, vec![]);
                 Ok(())
             } else {
                 return Err(ApiError::permission_denied("Caller not authorized to sign this contract"));
@@ -58,30 +411,252 @@ impl<T: ContractRepository, U: WalletService> ContractService for ContractServic
         }
     }
 
+    /// Sign the current version of a contract on behalf of a signatory identified by its Ethereum address.
+    /// The signature must be an EIP-712 signature of SignContract(contractId, contentHash, version)
+    /// recovering to that address; the caller only relays it.
+    fn sign_contract_with_evm(&self, contract_id: String, content_hash: String, evm_signature: EvmSignature, caller: Principal) -> Result<(), ApiError> {
+        let contract_uuid = Uuid::try_from(contract_id.as_str())?;
+        let contract = self.contract_repository.get_contract(contract_uuid)
+            .ok_or_else(|| ApiError::not_found("Contract not found"))?;
+
+        let address = evm_signature.address.parse::<Address>()
+            .map_err(|e| ApiError::invalid_argument(&format!("Invalid Ethereum address: {}", e)))?;
+        let signatory = contract.evm_signatory(&address.to_string())
+            .ok_or_else(|| ApiError::permission_denied("Address not authorized to sign this contract"))?;
+        self.check_signable(&contract, signatory, &content_hash)?;
+        let mut subjects = vec![DenylistSubject::EvmAddress(address.to_string()), DenylistSubject::Principal(caller)];
+        subjects.extend(signatory.principal.map(DenylistSubject::Principal));
+        self.denylist_service.screen(&subjects, caller, Some(contract_uuid), "Signing")?;
+        self.verification_service.assert_verified(signatory.principal, contract.terms.price)?;
+
+        let signature_bytes = hex::decode(evm_signature.signature.trim_start_matches("0x"))
+            .map_err(|e| ApiError::invalid_argument(&format!("Invalid signature: {}", e)))?;
+        let signature = Signature::try_from(signature_bytes.as_slice())
+            .map_err(|e| ApiError::invalid_argument(&format!("Invalid signature: {}", e)))?;
+        let content_hash_bytes = hex::decode(&contract.content_hash)
+            .ok()
+            .and_then(|hash| FixedBytes::<32>::try_from(hash.as_slice()).ok())
+            .ok_or_else(|| ApiError::internal("Invalid content hash"))?;
+
+        let typed_data = eip712::SignContract {
+            contractId: FixedBytes::<16>::from_slice(&contract_uuid.to_bytes()),
+            contentHash: content_hash_bytes,
+            version: contract.version,
+        };
+        let digest = typed_data.eip712_signing_hash(&eip712::domain(self.wallet_service.chain_id()));
+        let signer = signature.recover_address_from_prehash(&digest)
+            .map_err(|e| ApiError::invalid_argument(&format!("Invalid signature: {}", e)))?;
+        if signer != address {
+            return Err(ApiError::permission_denied("Signature was not made by the signatory's address"));
+        }
+
+        let signature = ContractSignature::new(&contract, None)
+            .with_evm_signature(format!("0x{}", hex::encode(signature_bytes)));
+        self.contract_repository.update_contract_signature(contract_uuid, SignatoryId::EvmAddress(address.to_string()), Some(signature), caller);
+        self.record_contract_change(contract_uuid, caller, AuditAction::ContractSigned, Some(&contract), Some(format!("signed with Ethereum address {}", address)));
+        self.append_contract_block(contract_uuid, caller, BTYPE_CONTRACT_SIGNED, vec![
+            ("evm_address".to_string(), ICRC3Value::from(address.to_string())),
+        ]);
+        Ok(())
+    }
+
+    /// Withdraw the caller's signature while the contract's quorum is not met yet
+    fn revoke_signature(&self, contract_id: String, caller: Principal) -> Result<(), ApiError> {
+        let contract_id = Uuid::try_from(contract_id.as_str())?;
+        let contract = self.contract_repository.get_contract(contract_id)
+            .ok_or_else(|| ApiError::not_found("Contract not found"))?;
+
+        let signatory = contract.signatory(&caller)
+            .ok_or_else(|| ApiError::permission_denied("Caller not authorized to revoke a signature on this contract"))?;
+
+        if contract.is_signed() {
+            return Err(ApiError::conflict("Signatures cannot be revoked once the contract is fully signed"));
+        }
+
+        if !signatory.has_signed() {
+            return Err(ApiError::conflict("Caller has not signed this contract"));
+        }
+
+        self.contract_repository.update_contract_signature(contract_id, SignatoryId::Principal(caller), None, caller);
+        self.record_contract_change(contract_id, caller, AuditAction::SignatureRevoked, Some(&contract), None);
+        self.append_contract_block(contract_id, caller, BTYPE_SIGNATURE_REVOKED, vec![]);
+        Ok(())
+    }
+
     /// Query a contract by its ID
-    fn get_contract(&self, contract_id: String) -> Option<Contract> {
-        if let Ok(contract_id) = Uuid::try_from(contract_id.as_str()) {
-            self.contract_repository.get_contract(contract_id)
-        } else {
-            None
+    fn get_contract(&self, contract_id: String) -> Result<Contract, ApiError> {
+        let contract_id = Uuid::try_from(contract_id.as_str())?;
+        self.contract_repository.get_contract(contract_id)
+            .ok_or_else(|| ApiError::not_found("Contract not found"))
+    }
+
+    /// Query a contract with a certificate and a witness for its hash, only available in query calls
+    fn get_certified_contract(&self, contract_id: String) -> Result<CertifiedContract, ApiError> {
+        let contract = self.get_contract(contract_id.clone())?;
+        let certificate = ic_cdk::api::data_certificate()
+            .ok_or_else(|| ApiError::invalid_argument("Certificates are only available in query calls"))?;
+        let witness = encode_hash_tree(&contract_witness(&contract_id))?;
+
+        Ok(CertifiedContract {
+            encoded_contract: ByteBuf::from(contract.encoded()),
+            contract,
+            certificate: ByteBuf::from(certificate),
+            witness: ByteBuf::from(witness),
+        })
+    }
+
+    /// Query the signature status of a contract with a certificate and a witness, only available in query calls
+    fn get_certified_signature_status(&self, contract_id: String) -> Result<CertifiedSignatureStatus, ApiError> {
+        let contract = self.get_contract(contract_id.clone())?;
+        let certificate = ic_cdk::api::data_certificate()
+            .ok_or_else(|| ApiError::invalid_argument("Certificates are only available in query calls"))?;
+        let witness = encode_hash_tree(&signed_witness(&contract_id))?;
+
+        Ok(CertifiedSignatureStatus {
+            is_signed: contract.is_signed(),
+            certificate: ByteBuf::from(certificate),
+            witness: ByteBuf::from(witness),
+        })
+    }
+
+    /// Rebuild the certified tree from the persisted hashes, e.g. after an upgrade.
+    /// Contracts are only decoded if no hash has been persisted yet, i.e. once after upgrading
+    /// from a canister that did not persist them.
+    fn certify_contracts(&self) {
+        let mut hashes = self.contract_repository.list_certified_hashes();
+        if hashes.is_empty() {
+            for (contract_id, contract) in self.contract_repository.list_contracts() {
+                let certified = contract.certified();
+                self.contract_repository.set_certified_hash(contract_id, certified.clone());
+                hashes.push((contract_id, certified));
+            }
+        }
+
+        for (contract_id, certified) in hashes {
+            set_certified_contract(&contract_id.to_string(), certified.hash, certified.is_signed);
         }
+        certify_data();
     }
 
-    /// query signature status of a contract
-    fn is_signed(&self, contract_id: String) -> Result<bool, ApiError> {
+    /// Convert contracts stored by the two-party canister, see ContractRepository::migrate_legacy_contracts
+    fn migrate_legacy_contracts(&self) {
+        self.contract_repository.migrate_legacy_contracts();
+        self.contract_repository.index_contracts_by_creation();
+    }
+
+    /// Query the public summary of a contract, if enabled
+    fn get_contract_summary(&self, contract_id: String, config: &PublicSummaryConfig) -> Result<ContractSummary, ApiError> {
+        if !config.enabled {
+            return Err(ApiError::permission_denied("Public contract summaries are disabled"));
+        }
+
         let contract_id = Uuid::try_from(contract_id.as_str())?;
-        if let Some(contract) =  self.contract_repository.get_contract(contract_id) {
-            Ok(contract.is_signed())
+        let contract = self.contract_repository.get_contract(contract_id)
+            .ok_or_else(|| ApiError::not_found("Contract not found"))?;
+
+        Ok(ContractSummary::new(contract_id, &contract, config))
+    }
+
+    /// Query all versions of a contract json, oldest first
+    fn get_contract_versions(&self, contract_id: String) -> Result<Vec<ContractVersion>, ApiError> {
+        let contract_id = Uuid::try_from(contract_id.as_str())?;
+        if self.contract_repository.get_contract(contract_id).is_none() {
+            return Err(ApiError::not_found("Contract not found"));
+        }
+
+        Ok(self.contract_repository.get_contract_versions(contract_id))
+    }
+
+    /// List the contracts the caller is a signatory of, one page at a time
+    fn list_contracts(&self, request: ListContractsRequest, caller: Principal) -> Result<ContractPage, ApiError> {
+        let limit = request.limit.unwrap_or(DEFAULT_CONTRACTS_PAGE_SIZE).clamp(1, MAX_CONTRACTS_PAGE_SIZE) as usize;
+        let descending = matches!(request.sort.unwrap_or_default(), ContractSort::CreatedAtDesc);
+
+        // seek to the start of the created_at range unless the cursor is already past it
+        let range_start = if descending {
+            request.created_to
+                .and_then(|to| to.checked_add(1))
+                .map(|created_at| ContractCursor { created_at, contract_id: Uuid::MIN })
         } else {
-            Err(ApiError::not_found("Contract not found"))
+            request.created_from
+                .and_then(|from| from.checked_sub(1))
+                .map(|created_at| ContractCursor { created_at, contract_id: Uuid::MAX })
+        };
+        let mut position = match (request.cursor, range_start) {
+            (Some(cursor), Some(start)) if descending => Some(cursor.min(start)),
+            (Some(cursor), Some(start)) => Some(cursor.max(start)),
+            (cursor, start) => cursor.or(start),
+        };
+
+        let mut contracts: Vec<ContractListItem> = vec![];
+        let mut has_more = false;
+        while let Some((cursor, role)) = self.contract_repository.next_participant_contract(caller, position, descending) {
+            position = Some(cursor);
+
+            let past_range = if descending {
+                request.created_from.is_some_and(|from| cursor.created_at < from)
+            } else {
+                request.created_to.is_some_and(|to| cursor.created_at > to)
+            };
+            if past_range {
+                break;
+            }
+            if request.role.is_some_and(|r| r != role) {
+                continue;
+            }
+            let Some(contract) = self.contract_repository.get_contract(cursor.contract_id) else {
+                continue;
+            };
+            let item = ContractListItem::new(cursor.contract_id, role, &contract);
+            if request.status.is_some_and(|s| s != item.status) {
+                continue;
+            }
+
+            if contracts.len() == limit {
+                has_more = true;
+                break;
+            }
+            contracts.push(item);
         }
+
+        let next_cursor = if has_more {
+            contracts.last().map(ContractListItem::cursor)
+        } else {
+            None
+        };
+
+        Ok(ContractPage { contracts, next_cursor })
+    }
+
+    /// Derive the vetKey of an encrypted contract, encrypted under the caller's transport public key.
+    /// Keys are only released to signatories.
+    async fn get_contract_encryption_key(&self, contract_id: String, transport_public_key: Vec<u8>, caller: Principal) -> Result<Vec<u8>, ApiError> {
+        let contract_id = Uuid::try_from(contract_id.as_str())?;
+        let contract = self.contract_repository.get_contract(contract_id)
+            .ok_or_else(|| ApiError::not_found("Contract not found"))?;
+
+        if !contract.is_signatory(&caller) {
+            return Err(ApiError::permission_denied("Caller not authorized to decrypt this contract"));
+        }
+
+        let encryption = contract.encryption
+            .ok_or_else(|| ApiError::invalid_argument("Contract is not encrypted"))?;
+
+        vetkd_derive_key(CONTRACT_ENCRYPTION_CONTEXT.to_vec(), encryption.derivation_input(), transport_public_key).await
+    }
+
+    /// Get the vetKD public key used to encrypt contracts and verify derived keys
+    async fn get_encryption_public_key(&self) -> Result<Vec<u8>, ApiError> {
+        vetkd_public_key(CONTRACT_ENCRYPTION_CONTEXT.to_vec()).await
     }
 
-    async fn issue_payment(&self, contract_id: String, caller: Principal, address: Address, amount: u64) -> Result<(), ApiError>{
+    /// Transfer the contract price to the seller. caller is the principal issuing the payment
+    /// and is recorded as the actor; seller is the payee and has to pass screening and verification.
+    async fn issue_payment(&self, contract_id: String, seller: Principal, address: Address, amount: u64, caller: Principal) -> Result<(), ApiError>{
         let contract_id = Uuid::try_from(contract_id.as_str())?;
         if let Some(contract) = self.contract_repository.get_contract(contract_id) {
-            if caller != contract.signatories.seller.0 {
-                return Err(ApiError::permission_denied("Caller not authorized"));
+            if !contract.has_role(&seller, SignatoryRole::Seller) {
+                return Err(ApiError::invalid_argument("Payee is not a seller of the contract"));
             }
 
             if !contract.is_signed() {
@@ -92,14 +667,47 @@ impl<T: ContractRepository, U: WalletService> ContractService for ContractServic
                 return Err(ApiError::internal("Payment already issued"));
             }
 
+            if let Some(price) = contract.terms.price {
+                if amount != price {
+                    return Err(ApiError::invalid_argument(&format!("Amount {} does not match the contract price {}", amount, price)));
+                }
+            }
+
+            if let Some(currency) = &contract.terms.currency {
+                if !currency.eq_ignore_ascii_case("USDC") {
+                    return Err(ApiError::invalid_argument(&format!("Payments in {} are not supported", currency)));
+                }
+            }
+
+            if contract.terms.inspection_deadline().is_some_and(|deadline| ic_cdk::api::time() < deadline) {
+                return Err(ApiError::conflict("Payment cannot be issued before the inspection period has ended"));
+            }
+
+            self.denylist_service.screen(
+                &[DenylistSubject::Principal(seller), DenylistSubject::EvmAddress(address.to_string())],
+                caller,
+                Some(contract_id),
+                &format!("Payment of {} USDC", amount),
+            )?;
+            self.verification_service.assert_verified(Some(seller), Some(amount))?;
+
             //eagerly set payment status to true to prevent double spending
             self.contract_repository.update_payment_status(contract_id, true);
-            //TODO: validate amount against contract or use value stored in contract
+            self.record_contract_change(contract_id, caller, AuditAction::PaymentStatusUpdated, Some(&contract), Some(format!("{} USDC to {}", amount, address)));
             match self.wallet_service.transfer_usdc(amount, address).await{
-                Ok(_) => Ok(()),
+                Ok(_) => {
+                    self.append_contract_block(contract_id, caller, BTYPE_PAYMENT_ISSUED, vec![
+                        ("amount".to_string(), ICRC3Value::from(amount)),
+                        ("currency".to_string(), ICRC3Value::from("USDC")),
+                        ("to".to_string(), ICRC3Value::from(address.to_string())),
+                    ]);
+                    Ok(())
+                }
                 Err(e) => {
                     //rollback payment status if transfer failed
+                    let paid = self.contract_repository.get_contract(contract_id);
                     self.contract_repository.update_payment_status(contract_id, false);
+                    self.record_contract_change(contract_id, caller, AuditAction::PaymentStatusUpdated, paid.as_ref(), Some("transfer failed".to_string()));
                     Err(ApiError::internal(format!("Transfer failed: {}", e).as_str()))
                 }
             }
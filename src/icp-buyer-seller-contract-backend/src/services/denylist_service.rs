@@ -0,0 +1,155 @@
+use alloy::primitives::Address;
+use candid::Principal;
+use crate::repositories::{
+    ApiError, AuditAction, AuditEntry, AuditRepository, AuditRepositoryImpl, DenylistEntry, DenylistRepository, DenylistRepositoryImpl,
+    DenylistSubject, Uuid, MAX_DENYLIST_IMPORT_SIZE, MAX_DENYLIST_REASON_SIZE,
+};
+
+pub trait DenylistService {
+    fn import_denylist(&self, entries: Vec<(DenylistSubject, String)>, caller: Principal) -> Result<u32, ApiError>;
+    fn remove_from_denylist(&self, subjects: Vec<DenylistSubject>, caller: Principal) -> Result<u32, ApiError>;
+    fn list_denylist(&self) -> Vec<(DenylistSubject, DenylistEntry)>;
+    fn screen(&self, subjects: &[DenylistSubject], actor: Principal, contract_id: Option<Uuid>, attempt: &str) -> Result<(), ApiError>;
+}
+
+pub struct DenylistServiceImpl<T: DenylistRepository, U: AuditRepository> {
+    denylist_repository: T,
+    audit_repository: U,
+}
+
+impl Default for DenylistServiceImpl<DenylistRepositoryImpl, AuditRepositoryImpl> {
+    fn default() -> Self {
+        Self::new(DenylistRepositoryImpl::default(), AuditRepositoryImpl::default())
+    }
+}
+
+impl<T: DenylistRepository, U: AuditRepository> DenylistServiceImpl<T, U> {
+    pub fn new(denylist_repository: T, audit_repository: U) -> Self {
+        Self { denylist_repository, audit_repository }
+    }
+
+    fn get_entry(&self, subject: &DenylistSubject) -> Option<DenylistEntry> {
+        match subject {
+            DenylistSubject::Principal(principal) => self.denylist_repository.get_principal(principal),
+            // addresses are validated on import, an unparsable address cannot be listed
+            DenylistSubject::EvmAddress(address) => address.parse::<Address>().ok()
+                .and_then(|address| self.denylist_repository.get_address(address)),
+        }
+    }
+
+    fn audit(&self, actor: Principal, action: AuditAction, subject: &DenylistSubject, contract_id: Option<Uuid>, before: Option<String>, after: Option<String>) {
+        let mut entry = AuditEntry::new(actor, action).with_change(before, after);
+        if let DenylistSubject::Principal(principal) = subject {
+            entry = entry.with_subject(*principal);
+        }
+        if let Some(contract_id) = contract_id {
+            entry = entry.with_contract(contract_id);
+        }
+        self.audit_repository.append_entry(entry);
+    }
+}
+
+fn parse_address(address: &str) -> Result<Address, ApiError> {
+    address.parse::<Address>()
+        .map_err(|e| ApiError::invalid_argument(&format!("Invalid Ethereum address {}: {}", address, e)))
+}
+
+impl<T: DenylistRepository, U: AuditRepository> DenylistService for DenylistServiceImpl<T, U> {
+    /// Add principals and addresses to the denylist, replacing the reason of subjects already listed.
+    /// The whole import is rejected if any entry is invalid.
+    fn import_denylist(&self, entries: Vec<(DenylistSubject, String)>, caller: Principal) -> Result<u32, ApiError> {
+        if entries.is_empty() || entries.len() > MAX_DENYLIST_IMPORT_SIZE {
+            return Err(ApiError::invalid_argument(&format!(
+                "An import must contain between 1 and {} entries",
+                MAX_DENYLIST_IMPORT_SIZE
+            )));
+        }
+        for (subject, reason) in &entries {
+            if reason.len() > MAX_DENYLIST_REASON_SIZE {
+                return Err(ApiError::invalid_argument(&format!(
+                    "Reason for {} exceeds the maximum size of {} bytes",
+                    subject,
+                    MAX_DENYLIST_REASON_SIZE
+                )));
+            }
+            if let DenylistSubject::EvmAddress(address) = subject {
+                parse_address(address)?;
+            }
+        }
+
+        let now = ic_cdk::api::time();
+        for (subject, reason) in &entries {
+            let before = self.get_entry(subject);
+            let entry = DenylistEntry { reason: reason.clone(), added_by: caller, added_at: now };
+            let subject = match subject {
+                DenylistSubject::Principal(principal) => {
+                    self.denylist_repository.add_principal(*principal, entry);
+                    subject.clone()
+                }
+                DenylistSubject::EvmAddress(address) => {
+                    let address = parse_address(address)?;
+                    self.denylist_repository.add_address(address, entry);
+                    DenylistSubject::EvmAddress(address.to_string())
+                }
+            };
+            self.audit(
+                caller,
+                AuditAction::DenylistUpdated,
+                &subject,
+                None,
+                before.map(|entry| format!("{} listed: {}", subject, entry.reason)),
+                Some(format!("{} listed: {}", subject, reason)),
+            );
+        }
+
+        Ok(entries.len() as u32)
+    }
+
+    /// Remove principals and addresses from the denylist, returning how many were listed
+    fn remove_from_denylist(&self, subjects: Vec<DenylistSubject>, caller: Principal) -> Result<u32, ApiError> {
+        if subjects.len() > MAX_DENYLIST_IMPORT_SIZE {
+            return Err(ApiError::invalid_argument(&format!(
+                "At most {} entries can be removed at once",
+                MAX_DENYLIST_IMPORT_SIZE
+            )));
+        }
+
+        let mut removed = 0;
+        for subject in subjects {
+            let before = match &subject {
+                DenylistSubject::Principal(principal) => self.denylist_repository.remove_principal(principal),
+                DenylistSubject::EvmAddress(address) => self.denylist_repository.remove_address(parse_address(address)?),
+            };
+            if let Some(before) = before {
+                removed += 1;
+                self.audit(caller, AuditAction::DenylistUpdated, &subject, None, Some(format!("{} listed: {}", subject, before.reason)), None);
+            }
+        }
+
+        Ok(removed)
+    }
+
+    fn list_denylist(&self) -> Vec<(DenylistSubject, DenylistEntry)> {
+        let principals = self.denylist_repository.list_principals()
+            .into_iter()
+            .map(|(principal, entry)| (DenylistSubject::Principal(principal), entry));
+        let addresses = self.denylist_repository.list_addresses()
+            .into_iter()
+            .map(|(address, entry)| (DenylistSubject::EvmAddress(address.to_string()), entry));
+
+        principals.chain(addresses).collect()
+    }
+
+    /// Fail with a blocked error if any of the subjects is on the denylist.
+    /// The blocked attempt is recorded in the audit log, attempt describes what was tried.
+    fn screen(&self, subjects: &[DenylistSubject], actor: Principal, contract_id: Option<Uuid>, attempt: &str) -> Result<(), ApiError> {
+        for subject in subjects {
+            if self.get_entry(subject).is_some() {
+                self.audit(actor, AuditAction::DenylistBlocked, subject, contract_id, None, Some(format!("{} blocked, {} is denylisted", attempt, subject)));
+                return Err(ApiError::blocked(&format!("{} is denylisted", subject)));
+            }
+        }
+
+        Ok(())
+    }
+}
\ No newline at end of file
@@ -0,0 +1,30 @@
+use alloy::sol;
+use alloy::sol_types::{eip712_domain, Eip712Domain};
+
+// EIP-712 typed data signed by the canister and by parties signing with an Ethereum wallet.
+sol! {
+    struct ContractAttestation {
+        bytes16 contractId;
+        bytes32 contentHash;
+        string status;
+        uint32 version;
+        uint256 price;
+        string currency;
+        uint64 timestamp;
+    }
+
+    struct SignContract {
+        bytes16 contractId;
+        bytes32 contentHash;
+        uint32 version;
+    }
+}
+
+/// Domain of all typed data of the canister
+pub fn domain(chain_id: u64) -> Eip712Domain {
+    eip712_domain! {
+        name: "TradeOnChain",
+        version: "1",
+        chain_id: chain_id,
+    }
+}
\ No newline at end of file
@@ -0,0 +1,91 @@
+use crate::repositories::{ApiError, BlockRepository, BlockRepositoryImpl, BlockWithId, GetBlocksArgs, GetBlocksResult, ICRC3DataCertificate, SupportedBlockType, MAX_BLOCKS_PER_REQUEST, SUPPORTED_BLOCK_TYPES};
+use crate::system_api::{certify_icrc3_tip, encode_hash_tree, icrc3_tip_witness};
+use candid::Nat;
+use serde_bytes::ByteBuf;
+
+const BLOCK_TYPES_URL: &str = "https://github.com/m4xsimo/tradeonchain-icp#icrc-3-block-log";
+
+pub trait ICRC3Service {
+    fn get_blocks(&self, args: Vec<GetBlocksArgs>) -> GetBlocksResult;
+    fn get_tip_certificate(&self) -> Result<Option<ICRC3DataCertificate>, ApiError>;
+    fn supported_block_types(&self) -> Vec<SupportedBlockType>;
+    fn certify_tip(&self);
+}
+
+pub struct ICRC3ServiceImpl<T: BlockRepository> {
+    block_repository: T,
+}
+
+impl Default for ICRC3ServiceImpl<BlockRepositoryImpl> {
+    fn default() -> Self {
+        Self::new(BlockRepositoryImpl::default())
+    }
+}
+
+impl<T: BlockRepository> ICRC3ServiceImpl<T> {
+    pub fn new(block_repository: T) -> Self {
+        Self { block_repository }
+    }
+}
+
+impl<T: BlockRepository> ICRC3Service for ICRC3ServiceImpl<T> {
+    /// Query ranges of blocks, at most MAX_BLOCKS_PER_REQUEST blocks are returned in total
+    fn get_blocks(&self, args: Vec<GetBlocksArgs>) -> GetBlocksResult {
+        let log_length = self.block_repository.log_length();
+
+        let mut blocks = vec![];
+        for arg in args {
+            let start = u64::try_from(&arg.start.0).unwrap_or(u64::MAX);
+            let length = u64::try_from(&arg.length.0).unwrap_or(u64::MAX);
+            let end = start.saturating_add(length).min(log_length);
+
+            for index in start..end {
+                if blocks.len() as u64 >= MAX_BLOCKS_PER_REQUEST {
+                    break;
+                }
+                if let Some(block) = self.block_repository.get_block(index) {
+                    blocks.push(BlockWithId { id: Nat::from(index), block });
+                }
+            }
+        }
+
+        GetBlocksResult {
+            log_length: Nat::from(log_length),
+            blocks,
+            archived_blocks: vec![],
+        }
+    }
+
+    /// Certificate and hash tree of the last block index and hash, none before the first block
+    fn get_tip_certificate(&self) -> Result<Option<ICRC3DataCertificate>, ApiError> {
+        let Some(certificate) = ic_cdk::api::data_certificate() else {
+            return Ok(None);
+        };
+        if self.block_repository.get_tip().is_none() {
+            return Ok(None);
+        }
+
+        let hash_tree = encode_hash_tree(&icrc3_tip_witness())?;
+        Ok(Some(ICRC3DataCertificate {
+            certificate: ByteBuf::from(certificate),
+            hash_tree: ByteBuf::from(hash_tree),
+        }))
+    }
+
+    fn supported_block_types(&self) -> Vec<SupportedBlockType> {
+        SUPPORTED_BLOCK_TYPES
+            .iter()
+            .map(|block_type| SupportedBlockType {
+                block_type: block_type.to_string(),
+                url: BLOCK_TYPES_URL.to_string(),
+            })
+            .collect()
+    }
+
+    /// Set the certified data from the current tip, e.g. after an upgrade
+    fn certify_tip(&self) {
+        if let Some((index, hash)) = self.block_repository.get_tip() {
+            certify_icrc3_tip(index, hash);
+        }
+    }
+}
\ No newline at end of file
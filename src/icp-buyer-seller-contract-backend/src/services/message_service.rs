@@ -0,0 +1,78 @@
+use candid::Principal;
+use crate::repositories::{ApiError, AttachmentRepository, AttachmentRepositoryImpl, ContractMessage, ContractRepository, ContractRepositoryImpl, MessagePage, MessageRepository, MessageRepositoryImpl, Uuid, DEFAULT_MESSAGES_PAGE_SIZE, MAX_MESSAGES_PAGE_SIZE};
+
+pub trait MessageService {
+    fn post_message(&self, contract_id: String, text: String, attachment_id: Option<String>, caller: Principal) -> Result<u64, ApiError>;
+    fn list_messages(&self, contract_id: String, start: Option<u64>, limit: Option<u32>, caller: Principal) -> Result<MessagePage, ApiError>;
+}
+
+pub struct MessageServiceImpl<T: MessageRepository, U: ContractRepository, V: AttachmentRepository> {
+    message_repository: T,
+    contract_repository: U,
+    attachment_repository: V,
+}
+
+impl Default for MessageServiceImpl<MessageRepositoryImpl, ContractRepositoryImpl, AttachmentRepositoryImpl> {
+    fn default() -> Self {
+        Self::new(MessageRepositoryImpl::default(), ContractRepositoryImpl::default(), AttachmentRepositoryImpl::default())
+    }
+}
+
+impl<T: MessageRepository, U: ContractRepository, V: AttachmentRepository> MessageServiceImpl<T, U, V> {
+    pub fn new(message_repository: T, contract_repository: U, attachment_repository: V) -> Self {
+        Self { message_repository, contract_repository, attachment_repository }
+    }
+
+    /// Message threads are only accessible to the signatories of a contract
+    fn assert_participant(&self, contract_id: Uuid, caller: &Principal) -> Result<(), ApiError> {
+        let contract = self.contract_repository.get_contract(contract_id)
+            .ok_or_else(|| ApiError::not_found("Contract not found"))?;
+
+        if !contract.is_signatory(caller) {
+            return Err(ApiError::permission_denied("Caller not authorized to access the messages of this contract"));
+        }
+
+        Ok(())
+    }
+}
+
+impl<T: MessageRepository, U: ContractRepository, V: AttachmentRepository> MessageService for MessageServiceImpl<T, U, V> {
+    /// Append a message to the thread of a contract, optionally referencing one of its attachments
+    fn post_message(&self, contract_id: String, text: String, attachment_id: Option<String>, caller: Principal) -> Result<u64, ApiError> {
+        let contract_id = Uuid::try_from(contract_id.as_str())?;
+        self.assert_participant(contract_id, &caller)?;
+        ContractMessage::validate_text(&text)?;
+
+        let attachment_id = match attachment_id {
+            Some(attachment_id) => {
+                let attachment_id = Uuid::try_from(attachment_id.as_str())?;
+                if self.attachment_repository.get_attachment(contract_id, attachment_id).is_none() {
+                    return Err(ApiError::not_found("Attachment not found"));
+                }
+                Some(attachment_id)
+            }
+            None => None,
+        };
+
+        Ok(self.message_repository.add_message(contract_id, caller, text, attachment_id))
+    }
+
+    /// Query the messages of a contract in the order they were posted, starting at index start
+    fn list_messages(&self, contract_id: String, start: Option<u64>, limit: Option<u32>, caller: Principal) -> Result<MessagePage, ApiError> {
+        let contract_id = Uuid::try_from(contract_id.as_str())?;
+        self.assert_participant(contract_id, &caller)?;
+
+        let limit = limit.unwrap_or(DEFAULT_MESSAGES_PAGE_SIZE).clamp(1, MAX_MESSAGES_PAGE_SIZE) as usize;
+        // fetch one more message to know whether there is a next page
+        let mut messages = self.message_repository.list_messages(contract_id, start.unwrap_or(0), limit + 1);
+
+        let next_index = if messages.len() > limit {
+            messages.truncate(limit);
+            messages.last().map(|message| message.index + 1)
+        } else {
+            None
+        };
+
+        Ok(MessagePage { messages, next_index })
+    }
+}
\ No newline at end of file
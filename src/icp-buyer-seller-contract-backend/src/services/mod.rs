@@ -1,9 +1,36 @@
 pub mod wallet_service;
 mod contract_service;
+mod eip712;
 mod user_service;
 mod access_control_service;
+mod anchor_service;
+mod attestation_service;
+mod attachment_service;
+mod audit_service;
+mod config_service;
+mod denylist_service;
+mod icrc3_service;
+mod message_service;
+mod profile_service;
+mod schema_service;
+mod siwe_service;
+mod template_service;
+mod verification_service;
 
 pub use wallet_service::*;
 pub use contract_service::*;
 pub use user_service::*;
-pub use access_control_service::*;
\ No newline at end of file
+pub use access_control_service::*;
+pub use anchor_service::*;
+pub use attestation_service::*;
+pub use attachment_service::*;
+pub use audit_service::*;
+pub use config_service::*;
+pub use denylist_service::*;
+pub use icrc3_service::*;
+pub use message_service::*;
+pub use profile_service::*;
+pub use schema_service::*;
+pub use siwe_service::*;
+pub use template_service::*;
+pub use verification_service::*;
\ No newline at end of file
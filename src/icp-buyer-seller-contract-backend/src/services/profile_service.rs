@@ -0,0 +1,119 @@
+use alloy::primitives::{Address, Signature};
+use candid::Principal;
+use crate::repositories::{ApiError, Contract, PayoutAddress, Profile, ProfileRepository, ProfileRepositoryImpl, ProfileUpdate, SiweRepository, SiweRepositoryImpl, MAX_PAYOUT_ADDRESSES, payout_address_message};
+
+pub trait ProfileService {
+    fn get_profile(&self, principal: Principal) -> Result<Profile, ApiError>;
+    fn update_profile(&self, update: ProfileUpdate, caller: Principal) -> Result<(), ApiError>;
+    fn add_payout_address(&self, address: String, signature: String, caller: Principal) -> Result<(), ApiError>;
+    fn remove_payout_address(&self, address: String, caller: Principal) -> Result<(), ApiError>;
+    fn get_signatory_profiles(&self, contract: &Contract) -> Vec<(Principal, Profile)>;
+    fn get_payout_address(&self, principal: Principal) -> Result<String, ApiError>;
+}
+
+pub struct ProfileServiceImpl<T: ProfileRepository, U: SiweRepository> {
+    profile_repository: T,
+    siwe_repository: U,
+}
+
+impl Default for ProfileServiceImpl<ProfileRepositoryImpl, SiweRepositoryImpl> {
+    fn default() -> Self {
+        Self::new(ProfileRepositoryImpl::default(), SiweRepositoryImpl::default())
+    }
+}
+
+impl<T: ProfileRepository, U: SiweRepository> ProfileServiceImpl<T, U> {
+    pub fn new(profile_repository: T, siwe_repository: U) -> Self {
+        Self { profile_repository, siwe_repository }
+    }
+}
+
+fn parse_address(address: &str) -> Result<Address, ApiError> {
+    address.parse::<Address>()
+        .map_err(|e| ApiError::invalid_argument(&format!("Invalid Ethereum address: {}", e)))
+}
+
+impl<T: ProfileRepository, U: SiweRepository> ProfileService for ProfileServiceImpl<T, U> {
+    fn get_profile(&self, principal: Principal) -> Result<Profile, ApiError> {
+        self.profile_repository.get_profile(&principal)
+            .ok_or_else(|| ApiError::not_found("Profile not found"))
+    }
+
+    /// Create or update the caller's profile, payout addresses are kept
+    fn update_profile(&self, update: ProfileUpdate, caller: Principal) -> Result<(), ApiError> {
+        update.validate()?;
+
+        let mut profile = self.profile_repository.get_profile(&caller).unwrap_or_default();
+        profile.apply(update);
+        self.profile_repository.save_profile(caller, profile);
+        Ok(())
+    }
+
+    /// Add a payout address to the caller's profile.
+    /// signature is the personal_sign signature of payout_address_message by the address.
+    fn add_payout_address(&self, address: String, signature: String, caller: Principal) -> Result<(), ApiError> {
+        let address = parse_address(&address)?;
+        let mut profile = self.profile_repository.get_profile(&caller).unwrap_or_default();
+
+        if profile.has_payout_address(&address.to_string()) {
+            return Err(ApiError::conflict("Payout address is already registered"));
+        }
+        if profile.payout_addresses.len() >= MAX_PAYOUT_ADDRESSES {
+            return Err(ApiError::invalid_argument(&format!(
+                "A profile can have at most {} payout addresses",
+                MAX_PAYOUT_ADDRESSES
+            )));
+        }
+
+        let signature_bytes = hex::decode(signature.trim_start_matches("0x"))
+            .map_err(|e| ApiError::invalid_argument(&format!("Invalid signature: {}", e)))?;
+        let signature = Signature::try_from(signature_bytes.as_slice())
+            .map_err(|e| ApiError::invalid_argument(&format!("Invalid signature: {}", e)))?;
+        let signer = signature.recover_address_from_msg(payout_address_message(&address.to_string(), &caller))
+            .map_err(|e| ApiError::invalid_argument(&format!("Invalid signature: {}", e)))?;
+        if signer != address {
+            return Err(ApiError::permission_denied("Signature was not made by the payout address"));
+        }
+
+        profile.payout_addresses.push(PayoutAddress {
+            address: address.to_string(),
+            verified_at: ic_cdk::api::time(),
+        });
+        profile.updated_at = ic_cdk::api::time();
+        self.profile_repository.save_profile(caller, profile);
+        Ok(())
+    }
+
+    fn remove_payout_address(&self, address: String, caller: Principal) -> Result<(), ApiError> {
+        let address = parse_address(&address)?.to_string();
+        let mut profile = self.get_profile(caller)?;
+
+        if !profile.has_payout_address(&address) {
+            return Err(ApiError::not_found("Payout address not found"));
+        }
+
+        profile.payout_addresses.retain(|payout| payout.address != address);
+        profile.updated_at = ic_cdk::api::time();
+        self.profile_repository.save_profile(caller, profile);
+        Ok(())
+    }
+
+    /// Query the profiles of the signatories of a contract, signatories without a profile are left out
+    fn get_signatory_profiles(&self, contract: &Contract) -> Vec<(Principal, Profile)> {
+        contract
+            .signatories
+            .iter()
+            .filter_map(|signatory| signatory.principal)
+            .filter_map(|principal| self.profile_repository.get_profile(&principal).map(|profile| (principal, profile)))
+            .collect()
+    }
+
+    /// Address payments to a principal are sent to when none is given:
+    /// its first verified payout address, or else the address it logged in with
+    fn get_payout_address(&self, principal: Principal) -> Result<String, ApiError> {
+        self.profile_repository.get_profile(&principal)
+            .and_then(|profile| profile.payout_addresses.first().map(|payout| payout.address.clone()))
+            .or_else(|| self.siwe_repository.get_address_by_principal(&principal).map(|address| address.to_string()))
+            .ok_or_else(|| ApiError::not_found("No payout address known for this principal"))
+    }
+}
\ No newline at end of file
@@ -0,0 +1,51 @@
+use candid::Principal;
+use crate::repositories::{ApiError, AuditAction, AuditEntry, AuditRepository, AuditRepositoryImpl, ContractSchema, SchemaRepository, SchemaRepositoryImpl};
+
+pub trait SchemaService {
+    fn register_contract_schema(&self, schema_json: String, caller: Principal) -> Result<u32, ApiError>;
+    fn get_contract_schema(&self, version: Option<u32>) -> Option<ContractSchema>;
+    fn list_contract_schemas(&self) -> Vec<ContractSchema>;
+}
+
+pub struct SchemaServiceImpl<T: SchemaRepository, U: AuditRepository> {
+    schema_repository: T,
+    audit_repository: U,
+}
+
+impl Default for SchemaServiceImpl<SchemaRepositoryImpl, AuditRepositoryImpl> {
+    fn default() -> Self {
+        Self::new(SchemaRepositoryImpl::default(), AuditRepositoryImpl::default())
+    }
+}
+
+impl<T: SchemaRepository, U: AuditRepository> SchemaServiceImpl<T, U> {
+    pub fn new(schema_repository: T, audit_repository: U) -> Self {
+        Self { schema_repository, audit_repository }
+    }
+}
+
+impl<T: SchemaRepository, U: AuditRepository> SchemaService for SchemaServiceImpl<T, U> {
+    /// Register a new version of the contract schema, which applies to all contracts created afterwards
+    fn register_contract_schema(&self, schema_json: String, caller: Principal) -> Result<u32, ApiError> {
+        ContractSchema::compile(&schema_json)?;
+
+        let version = self.schema_repository.add_schema(schema_json, caller);
+        let entry = AuditEntry::new(caller, AuditAction::SchemaRegistered)
+            .with_change((version > 1).then(|| format!("version {}", version - 1)), Some(format!("version {}", version)));
+        self.audit_repository.append_entry(entry);
+
+        Ok(version)
+    }
+
+    /// Query a schema by version, or the active schema if no version is given
+    fn get_contract_schema(&self, version: Option<u32>) -> Option<ContractSchema> {
+        match version {
+            Some(version) => self.schema_repository.get_schema(version),
+            None => self.schema_repository.get_latest_schema(),
+        }
+    }
+
+    fn list_contract_schemas(&self) -> Vec<ContractSchema> {
+        self.schema_repository.list_schemas()
+    }
+}
\ No newline at end of file
@@ -0,0 +1,236 @@
+use alloy::primitives::{Address, Signature};
+use candid::Principal;
+use serde_bytes::ByteBuf;
+use sha2::{Digest, Sha256};
+use crate::repositories::{ApiError, AuditAction, AuditEntry, AuditRepository, AuditRepositoryImpl, ConfigRepository, ConfigRepositoryImpl, ContractRepository, ContractRepositoryImpl, Delegation, Hash, ICRC3Value, SignedDelegation, SiweConfig, SiweLoginDetails, SiweMessage, SiwePreparedLogin, SiweRepository, SiweRepositoryImpl, DEFAULT_SIWE_SESSION_EXPIRES_IN, MAX_PENDING_SIWE_MESSAGES, MAX_PENDING_SIWE_MESSAGES_PER_ADDRESS, MAX_SIWE_SESSION_EXPIRES_IN, SIWE_MESSAGE_TTL};
+use crate::system_api::{certify_data, certify_delegation, delegation_witness, encode_canister_signature, set_certified_contract, with_random_bytes};
+
+/// DER prefix of a canister signature public key: the algorithm identifier with OID 1.3.6.1.4.1.56387.1.2
+const CANISTER_SIG_OID: [u8; 14] = [0x30, 0x0c, 0x06, 0x0a, 0x2b, 0x06, 0x01, 0x04, 0x01, 0x83, 0xb8, 0x43, 0x01, 0x02];
+/// Domain separator of the seeds the canister signature keys of Ethereum addresses are derived from
+const SIWE_SEED_DOMAIN: &[u8] = b"tradeonchain-siwe";
+
+pub trait SiweService {
+    fn prepare_login(&self, address: String) -> Result<SiwePreparedLogin, ApiError>;
+    fn login(&self, signature: String, address: String, session_key: ByteBuf, nonce: String) -> Result<SiweLoginDetails, ApiError>;
+    fn get_delegation(&self, address: String, session_key: ByteBuf, expiration: u64) -> Result<SignedDelegation, ApiError>;
+    fn get_principal(&self, address: String) -> Result<Principal, ApiError>;
+    fn get_address(&self, principal: Principal) -> Result<String, ApiError>;
+}
+
+pub struct SiweServiceImpl<T: SiweRepository, U: ContractRepository, V: ConfigRepository, W: AuditRepository> {
+    siwe_repository: T,
+    contract_repository: U,
+    config_repository: V,
+    audit_repository: W,
+}
+
+impl Default for SiweServiceImpl<SiweRepositoryImpl, ContractRepositoryImpl, ConfigRepositoryImpl, AuditRepositoryImpl> {
+    fn default() -> Self {
+        Self::new(
+            SiweRepositoryImpl::default(),
+            ContractRepositoryImpl::default(),
+            ConfigRepositoryImpl::default(),
+            AuditRepositoryImpl::default(),
+        )
+    }
+}
+
+impl<T: SiweRepository, U: ContractRepository, V: ConfigRepository, W: AuditRepository> SiweServiceImpl<T, U, V, W> {
+    pub fn new(siwe_repository: T, contract_repository: U, config_repository: V, audit_repository: W) -> Self {
+        Self { siwe_repository, contract_repository, config_repository, audit_repository }
+    }
+
+    fn siwe_config(&self) -> Result<SiweConfig, ApiError> {
+        self.config_repository.get_config().siwe
+            .ok_or_else(|| ApiError::invalid_argument("Sign-In with Ethereum is not enabled"))
+    }
+
+    /// Give the principal of a freshly logged in address to the contract signatories identified by that address
+    fn link_contracts(&self, address: Address, principal: Principal) {
+        let contract_ids = self.contract_repository.link_evm_signatory(address, principal);
+        for contract_id in contract_ids.iter() {
+            if let Some(contract) = self.contract_repository.get_contract(*contract_id) {
+                let certified = contract.certified();
+                set_certified_contract(&contract_id.to_string(), certified.hash, certified.is_signed);
+                self.contract_repository.set_certified_hash(*contract_id, certified);
+            }
+
+            let entry = AuditEntry::new(principal, AuditAction::EvmSignatoryLinked)
+                .with_contract(*contract_id)
+                .with_change(None, Some(format!("Ethereum address {} signs as {}", address, principal.to_text())));
+            self.audit_repository.append_entry(entry);
+        }
+        if !contract_ids.is_empty() {
+            certify_data();
+        }
+    }
+}
+
+fn parse_address(address: &str) -> Result<Address, ApiError> {
+    address.parse::<Address>()
+        .map_err(|e| ApiError::invalid_argument(&format!("Invalid Ethereum address: {}", e)))
+}
+
+/// Seed of the canister signature key of an address; the user's principal is derived from it
+fn address_seed(address: Address) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update(SIWE_SEED_DOMAIN);
+    hasher.update(address.as_slice());
+    hasher.finalize().into()
+}
+
+/// DER encoded canister signature public key: the canister id length, the canister id and the seed
+fn canister_sig_public_key(seed: &[u8]) -> Vec<u8> {
+    let canister_id = ic_cdk::id();
+    let canister_id = canister_id.as_slice();
+
+    let mut key = vec![canister_id.len() as u8];
+    key.extend_from_slice(canister_id);
+    key.extend_from_slice(seed);
+
+    // BIT STRING without unused bits
+    let mut bit_string = vec![0x03, key.len() as u8 + 1, 0x00];
+    bit_string.extend_from_slice(&key);
+
+    let mut der = vec![0x30, (CANISTER_SIG_OID.len() + bit_string.len()) as u8];
+    der.extend_from_slice(&CANISTER_SIG_OID);
+    der.extend_from_slice(&bit_string);
+    der
+}
+
+/// Representation independent hash of a delegation without targets, as signed by canister signatures
+fn delegation_hash(session_key: &[u8], expiration: u64) -> Hash {
+    ICRC3Value::Map(vec![
+        ("pubkey".to_string(), ICRC3Value::Blob(ByteBuf::from(session_key.to_vec()))),
+        ("expiration".to_string(), ICRC3Value::from(expiration)),
+    ])
+    .hash()
+}
+
+/// Hash of the message a canister signature of a delegation signs: the domain separator
+/// followed by the delegation hash. The signature is certified at sig/<sha256 of the seed>/<this hash>.
+fn delegation_signature_hash(session_key: &[u8], expiration: u64) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update(b"\x1Aic-request-auth-delegation");
+    hasher.update(delegation_hash(session_key, expiration));
+    hasher.finalize().into()
+}
+
+impl<T: SiweRepository, U: ContractRepository, V: ConfigRepository, W: AuditRepository> SiweService for SiweServiceImpl<T, U, V, W> {
+    /// Prepare the EIP-4361 message an address has to sign to log in.
+    /// Messages are identified by their nonce, so concurrent logins of an address do not replace each other.
+    /// Beyond the per address and the overall limit the oldest pending message is dropped rather than refusing the login.
+    fn prepare_login(&self, address: String) -> Result<SiwePreparedLogin, ApiError> {
+        let config = self.siwe_config()?;
+        let address = parse_address(&address)?;
+
+        let issued_at = ic_cdk::api::time();
+        self.siwe_repository.remove_expired_messages(issued_at);
+        let nonces = self.siwe_repository.list_message_nonces(&address.to_string());
+        for nonce in nonces.iter().take((nonces.len() + 1).saturating_sub(MAX_PENDING_SIWE_MESSAGES_PER_ADDRESS)) {
+            self.siwe_repository.remove_message(nonce);
+        }
+        if self.siwe_repository.count_messages() >= MAX_PENDING_SIWE_MESSAGES {
+            self.siwe_repository.remove_oldest_message();
+        }
+
+        let message = SiweMessage {
+            domain: config.domain,
+            address: address.to_string(),
+            statement: config.statement,
+            uri: config.uri,
+            chain_id: config.chain_id,
+            nonce: with_random_bytes(|bytes: [u8; 16]| hex::encode(bytes)),
+            issued_at,
+            expiration_time: issued_at + SIWE_MESSAGE_TTL,
+        };
+        let prepared = SiwePreparedLogin {
+            message: message.to_eip4361(),
+            nonce: message.nonce.clone(),
+        };
+        self.siwe_repository.add_message(message);
+
+        Ok(prepared)
+    }
+
+    /// Verify the signature of the prepared message and sign a delegation to session_key.
+    /// The delegation is fetched with get_delegation; the resulting principal is linked to the address.
+    fn login(&self, signature: String, address: String, session_key: ByteBuf, nonce: String) -> Result<SiweLoginDetails, ApiError> {
+        let config = self.siwe_config()?;
+        let address = parse_address(&address)?;
+        if session_key.is_empty() {
+            return Err(ApiError::invalid_argument("Session key must not be empty"));
+        }
+
+        let message = self.siwe_repository.get_message(&nonce)
+            .filter(|message| !message.is_expired() && message.address == address.to_string())
+            .ok_or_else(|| ApiError::not_found("No login prepared for this address and nonce"))?;
+
+        let signature_bytes = hex::decode(signature.trim_start_matches("0x"))
+            .map_err(|e| ApiError::invalid_argument(&format!("Invalid signature: {}", e)))?;
+        let signature = Signature::try_from(signature_bytes.as_slice())
+            .map_err(|e| ApiError::invalid_argument(&format!("Invalid signature: {}", e)))?;
+        let signer = signature.recover_address_from_msg(message.to_eip4361())
+            .map_err(|e| ApiError::invalid_argument(&format!("Invalid signature: {}", e)))?;
+        if signer != address {
+            return Err(ApiError::permission_denied("Signature was not made by the address"));
+        }
+        self.siwe_repository.remove_message(&nonce);
+
+        let expires_in = config.session_expires_in
+            .unwrap_or(DEFAULT_SIWE_SESSION_EXPIRES_IN)
+            .min(MAX_SIWE_SESSION_EXPIRES_IN);
+        let expiration = ic_cdk::api::time() + expires_in;
+
+        let seed = address_seed(address);
+        certify_delegation(
+            Sha256::digest(seed).into(),
+            delegation_signature_hash(&session_key, expiration),
+            expiration,
+        );
+
+        let user_canister_pubkey = canister_sig_public_key(&seed);
+        let principal = Principal::self_authenticating(&user_canister_pubkey);
+        self.siwe_repository.link_address(address, principal);
+        self.link_contracts(address, principal);
+
+        Ok(SiweLoginDetails {
+            expiration,
+            user_canister_pubkey: ByteBuf::from(user_canister_pubkey),
+        })
+    }
+
+    /// Get the delegation signed on login, must be called as a query to obtain a certificate
+    fn get_delegation(&self, address: String, session_key: ByteBuf, expiration: u64) -> Result<SignedDelegation, ApiError> {
+        let address = parse_address(&address)?;
+        if expiration < ic_cdk::api::time() {
+            return Err(ApiError::invalid_argument("Delegation has expired"));
+        }
+
+        let seed = address_seed(address);
+        let witness = delegation_witness(&Sha256::digest(seed).into(), &delegation_signature_hash(&session_key, expiration))
+            .ok_or_else(|| ApiError::not_found("No delegation signed for this session key"))?;
+        let certificate = ic_cdk::api::data_certificate()
+            .ok_or_else(|| ApiError::invalid_argument("Certificates are only available in query calls"))?;
+
+        Ok(SignedDelegation {
+            delegation: Delegation { pubkey: session_key, expiration, targets: None },
+            signature: ByteBuf::from(encode_canister_signature(&certificate, &witness)?),
+        })
+    }
+
+    /// Query the principal an address logged in as
+    fn get_principal(&self, address: String) -> Result<Principal, ApiError> {
+        let address = parse_address(&address)?;
+        self.siwe_repository.get_principal_by_address(address)
+            .ok_or_else(|| ApiError::not_found("Address has not logged in yet"))
+    }
+
+    /// Query the Ethereum address behind a principal obtained with SIWE
+    fn get_address(&self, principal: Principal) -> Result<String, ApiError> {
+        self.siwe_repository.get_address_by_principal(&principal)
+            .map(|address| address.to_string())
+            .ok_or_else(|| ApiError::not_found("Principal is not linked to an Ethereum address"))
+    }
+}
\ No newline at end of file
@@ -0,0 +1,85 @@
+use candid::Principal;
+use crate::repositories::{ApiError, AuditAction, AuditEntry, AuditRepository, AuditRepositoryImpl, ContractTemplate, TemplateReference, TemplateRepository, TemplateRepositoryImpl, Uuid};
+
+pub trait TemplateService {
+    fn register_template(&self, name: String, schema_json: String, body_json: String, defaults_json: Option<String>, caller: Principal) -> Result<Uuid, ApiError>;
+    fn update_template(&self, template_id: String, name: String, schema_json: String, body_json: String, defaults_json: Option<String>, caller: Principal) -> Result<u32, ApiError>;
+    fn get_template(&self, template_id: String, version: Option<u32>) -> Option<ContractTemplate>;
+    fn list_templates(&self) -> Vec<ContractTemplate>;
+    fn render_template(&self, template_id: String, params_json: String) -> Result<(String, TemplateReference), ApiError>;
+}
+
+pub struct TemplateServiceImpl<T: TemplateRepository, U: AuditRepository> {
+    template_repository: T,
+    audit_repository: U,
+}
+
+impl Default for TemplateServiceImpl<TemplateRepositoryImpl, AuditRepositoryImpl> {
+    fn default() -> Self {
+        Self::new(TemplateRepositoryImpl::default(), AuditRepositoryImpl::default())
+    }
+}
+
+impl<T: TemplateRepository, U: AuditRepository> TemplateServiceImpl<T, U> {
+    pub fn new(template_repository: T, audit_repository: U) -> Self {
+        Self { template_repository, audit_repository }
+    }
+
+    fn audit_template(&self, template: &ContractTemplate, before: Option<&ContractTemplate>, caller: Principal) {
+        let summary = |template: &ContractTemplate| {
+            format!("template {} version {} ({})", template.template_id, template.version, template.name)
+        };
+        let entry = AuditEntry::new(caller, AuditAction::TemplateRegistered)
+            .with_change(before.map(summary), Some(summary(template)));
+        self.audit_repository.append_entry(entry);
+    }
+}
+
+impl<T: TemplateRepository, U: AuditRepository> TemplateService for TemplateServiceImpl<T, U> {
+    /// Register a new template, stored as version 1
+    fn register_template(&self, name: String, schema_json: String, body_json: String, defaults_json: Option<String>, caller: Principal) -> Result<Uuid, ApiError> {
+        let template_id = Uuid::new();
+        let template = ContractTemplate::new(template_id, 1, name, schema_json, body_json, defaults_json, caller)?;
+        self.audit_template(&template, None, caller);
+        self.template_repository.add_template(template);
+
+        Ok(template_id)
+    }
+
+    /// Store a new version of an existing template; earlier versions are kept
+    fn update_template(&self, template_id: String, name: String, schema_json: String, body_json: String, defaults_json: Option<String>, caller: Principal) -> Result<u32, ApiError> {
+        let template_id = Uuid::try_from(template_id.as_str())?;
+        let latest = self.template_repository.get_latest_template(template_id)
+            .ok_or_else(|| ApiError::not_found("Template not found"))?;
+
+        let template = ContractTemplate::new(template_id, latest.version + 1, name, schema_json, body_json, defaults_json, caller)?;
+        let version = template.version;
+        self.audit_template(&template, Some(&latest), caller);
+        self.template_repository.add_template(template);
+
+        Ok(version)
+    }
+
+    /// Query a template by version, defaulting to its latest version
+    fn get_template(&self, template_id: String, version: Option<u32>) -> Option<ContractTemplate> {
+        let template_id = Uuid::try_from(template_id.as_str()).ok()?;
+        match version {
+            Some(version) => self.template_repository.get_template(template_id, version),
+            None => self.template_repository.get_latest_template(template_id),
+        }
+    }
+
+    fn list_templates(&self) -> Vec<ContractTemplate> {
+        self.template_repository.list_latest_templates()
+    }
+
+    /// Render the latest version of a template into a contract json,
+    /// returned with the template version that was used
+    fn render_template(&self, template_id: String, params_json: String) -> Result<(String, TemplateReference), ApiError> {
+        let template_id = Uuid::try_from(template_id.as_str())?;
+        let template = self.template_repository.get_latest_template(template_id)
+            .ok_or_else(|| ApiError::not_found("Template not found"))?;
+
+        Ok((template.render(&params_json)?, template.reference()))
+    }
+}
\ No newline at end of file
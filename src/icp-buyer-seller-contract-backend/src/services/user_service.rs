@@ -1,50 +1,162 @@
 use candid::Principal;
-use crate::repositories::{ApiError, User, UserRepository, UserRepositoryImpl};
+use crate::repositories::{ApiError, AuditAction, AuditEntry, AuditRepository, AuditRepositoryImpl, Permission, Role, User, UserRepository, UserRepositoryImpl};
 
 pub trait UserService {
     fn get_user_by_principal(&self, principal: &Principal) -> Result<(), ApiError>;
-    fn create_user(&mut self, principal: Principal, user: User) -> Result<(), ApiError>;
-    fn remove_user(&mut self, principal: &Principal) -> Result<(), ApiError>;
-    fn update_user(&mut self, principal: Principal, user: User) -> Result<(), ApiError>;
+    fn grant_role(&mut self, principal: Principal, role: Role, caller: Principal) -> Result<(), ApiError>;
+    fn revoke_role(&mut self, principal: &Principal, role: Option<Role>, caller: Principal) -> Result<(), ApiError>;
+    fn set_roles(&mut self, principal: Principal, roles: Vec<Role>, caller: Principal) -> Result<(), ApiError>;
     fn list_users(&self) -> Vec<(Principal, User)>;
+    fn get_permissions(&self, principal: &Principal) -> Vec<Permission>;
+    fn restore_admin(&mut self, principal: Principal, caller: Principal) -> Result<(), ApiError>;
 }
 
-pub struct UserServiceImpl<T: UserRepository> {
+pub struct UserServiceImpl<T: UserRepository, U: AuditRepository> {
     user_repository: T,
+    audit_repository: U,
 }
 
-impl Default for UserServiceImpl<UserRepositoryImpl> {
+impl Default for UserServiceImpl<UserRepositoryImpl, AuditRepositoryImpl> {
     fn default() -> Self {
-        Self::new(UserRepositoryImpl::default())
+        Self::new(UserRepositoryImpl::default(), AuditRepositoryImpl::default())
     }
 }
 
-impl<T: UserRepository> UserServiceImpl<T> {
-    pub fn new(user_repository: T) -> Self {
-        Self { user_repository }
+impl<T: UserRepository, U: AuditRepository> UserServiceImpl<T, U> {
+    pub fn new(user_repository: T, audit_repository: U) -> Self {
+        Self { user_repository, audit_repository }
+    }
+
+    /// Record a permission change in the audit log
+    fn audit_permission_change(&self, principal: Principal, action: AuditAction, before: Option<User>, caller: Principal) {
+        let after = self.user_repository.get_user_by_principal(&principal);
+        let entry = AuditEntry::new(caller, action)
+            .with_subject(principal)
+            .with_change(
+                before.map(|user| format!("roles {:?}", user.roles)),
+                after.map(|user| format!("roles {:?}", user.roles)),
+            );
+        self.audit_repository.append_entry(entry);
+    }
+
+    /// Fail if principal is the only admin and would lose the role, leaving nobody to manage users
+    fn check_keeps_admin(&self, principal: &Principal, remains_admin: bool) -> Result<(), ApiError> {
+        if remains_admin {
+            return Ok(());
+        }
+
+        let admins: Vec<Principal> = self.user_repository.list_users()
+            .into_iter()
+            .filter(|(_, user)| user.is_admin())
+            .map(|(principal, _)| principal)
+            .collect();
+        if admins.len() == 1 && admins[0] == *principal {
+            return Err(ApiError::conflict("The last admin cannot be removed or demoted"));
+        }
+
+        Ok(())
     }
 }
 
-impl<T: UserRepository> UserService for UserServiceImpl<T> {
+impl<T: UserRepository, U: AuditRepository> UserService for UserServiceImpl<T, U> {
     fn get_user_by_principal(&self, principal: &Principal) -> Result<(), ApiError> {
         self.user_repository.get_user_by_principal(principal)
         .ok_or_else(|| ApiError::not_found(format!("User with principal {} not found", principal).as_str()))?;
         Ok(())
     }
 
-    fn create_user(&mut self, principal: Principal, user: User) -> Result<(), ApiError> {
-        self.user_repository.create_user(principal, user)
+    /// Add a role to a principal, registering it as user if needed
+    fn grant_role(&mut self, principal: Principal, role: Role, caller: Principal) -> Result<(), ApiError> {
+        let Some(mut user) = self.user_repository.get_user_by_principal(&principal) else {
+            self.user_repository.create_user(principal, User::new(role))?;
+            self.audit_permission_change(principal, AuditAction::PermissionAdded, None, caller);
+            return Ok(());
+        };
+
+        if user.has_role(role) {
+            return Err(ApiError::conflict(&format!("Principal {} already has role {:?}", principal.to_text(), role)));
+        }
+
+        let before = user.clone();
+        user.roles.push(role);
+        self.user_repository.update_user(principal, user)?;
+        self.audit_permission_change(principal, AuditAction::PermissionUpdated, Some(before), caller);
+        Ok(())
     }
 
-    fn remove_user(&mut self, principal: &Principal) -> Result<(), ApiError> {
-        self.user_repository.remove_user(principal)
+    /// Remove a role from a principal, or all of them without role.
+    /// A principal left without roles is removed.
+    fn revoke_role(&mut self, principal: &Principal, role: Option<Role>, caller: Principal) -> Result<(), ApiError> {
+        let before = self.user_repository.get_user_by_principal(principal)
+            .ok_or_else(|| ApiError::not_found("User not found"))?;
+
+        let mut user = before.clone();
+        if let Some(role) = role {
+            if !user.has_role(role) {
+                return Err(ApiError::not_found(&format!("Principal {} does not have role {:?}", principal.to_text(), role)));
+            }
+            user.roles.retain(|other| *other != role);
+        } else {
+            user.roles.clear();
+        }
+        self.check_keeps_admin(principal, user.is_admin())?;
+
+        if user.roles.is_empty() {
+            self.user_repository.remove_user(principal)?;
+            self.audit_permission_change(*principal, AuditAction::PermissionRemoved, Some(before), caller);
+        } else {
+            self.user_repository.update_user(*principal, user)?;
+            self.audit_permission_change(*principal, AuditAction::PermissionUpdated, Some(before), caller);
+        }
+        Ok(())
     }
 
-    fn update_user(&mut self, principal: Principal, user: User) -> Result<(), ApiError> {
-        self.user_repository.update_user(principal, user)
+    /// Replace all roles of a principal
+    fn set_roles(&mut self, principal: Principal, roles: Vec<Role>, caller: Principal) -> Result<(), ApiError> {
+        if roles.is_empty() {
+            return Err(ApiError::invalid_argument("At least one role is required, use remove_permission to remove a user"));
+        }
+        let mut unique: Vec<Role> = vec![];
+        for role in roles {
+            if !unique.contains(&role) {
+                unique.push(role);
+            }
+        }
+        let roles = unique;
+        self.check_keeps_admin(&principal, roles.contains(&Role::Admin))?;
+
+        let before = self.user_repository.get_user_by_principal(&principal);
+        self.user_repository.update_user(principal, User { roles })?;
+        self.audit_permission_change(principal, AuditAction::PermissionUpdated, before, caller);
+        Ok(())
     }
 
     fn list_users(&self) -> Vec<(Principal, User)> {
         self.user_repository.list_users()
     }
+
+    fn get_permissions(&self, principal: &Principal) -> Vec<Permission> {
+        self.user_repository.get_user_by_principal(principal)
+            .map(|user| user.permissions())
+            .unwrap_or_default()
+    }
+
+    /// Make principal an admin again, keeping its other roles.
+    /// Meant for controllers to recover the canister's governance.
+    fn restore_admin(&mut self, principal: Principal, caller: Principal) -> Result<(), ApiError> {
+        if principal == Principal::anonymous() {
+            return Err(ApiError::invalid_argument("Anonymous principal cannot be admin"));
+        }
+
+        let before = self.user_repository.get_user_by_principal(&principal);
+        let mut user = before.clone().unwrap_or(User { roles: vec![] });
+        if user.is_admin() {
+            return Err(ApiError::conflict(&format!("Principal {} is already admin", principal.to_text())));
+        }
+
+        user.roles.push(Role::Admin);
+        self.user_repository.update_user(principal, user)?;
+        self.audit_permission_change(principal, AuditAction::AdminRestored, before, caller);
+        Ok(())
+    }
 }
\ No newline at end of file
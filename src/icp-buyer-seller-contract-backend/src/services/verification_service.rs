@@ -0,0 +1,127 @@
+use candid::Principal;
+use crate::repositories::{
+    ApiError, AuditAction, AuditEntry, AuditRepository, AuditRepositoryImpl, ConfigRepository, ConfigRepositoryImpl, Verification,
+    VerificationLevel, VerificationRepository, VerificationRepositoryImpl, MAX_PROVIDER_REFERENCE_SIZE, required_verification_level,
+};
+
+pub trait VerificationService {
+    fn get_verification(&self, principal: Principal) -> Result<Verification, ApiError>;
+    fn set_verification(
+        &self,
+        principal: Principal,
+        level: VerificationLevel,
+        provider_reference: String,
+        expires_at: Option<u64>,
+        caller: Principal,
+    ) -> Result<(), ApiError>;
+    fn remove_verification(&self, principal: Principal, caller: Principal) -> Result<(), ApiError>;
+    fn assert_verified(&self, principal: Option<Principal>, amount: Option<u64>) -> Result<(), ApiError>;
+}
+
+pub struct VerificationServiceImpl<T: VerificationRepository, U: ConfigRepository, V: AuditRepository> {
+    verification_repository: T,
+    config_repository: U,
+    audit_repository: V,
+}
+
+impl Default for VerificationServiceImpl<VerificationRepositoryImpl, ConfigRepositoryImpl, AuditRepositoryImpl> {
+    fn default() -> Self {
+        Self::new(VerificationRepositoryImpl::default(), ConfigRepositoryImpl::default(), AuditRepositoryImpl::default())
+    }
+}
+
+impl<T: VerificationRepository, U: ConfigRepository, V: AuditRepository> VerificationServiceImpl<T, U, V> {
+    pub fn new(verification_repository: T, config_repository: U, audit_repository: V) -> Self {
+        Self { verification_repository, config_repository, audit_repository }
+    }
+}
+
+fn describe(verification: &Verification) -> String {
+    format!(
+        "level {:?}, reference {}, expires at {}",
+        verification.level,
+        verification.provider_reference,
+        verification.expires_at.map_or("never".to_string(), |expires_at| expires_at.to_string())
+    )
+}
+
+impl<T: VerificationRepository, U: ConfigRepository, V: AuditRepository> VerificationService for VerificationServiceImpl<T, U, V> {
+    fn get_verification(&self, principal: Principal) -> Result<Verification, ApiError> {
+        self.verification_repository.get_verification(&principal)
+            .ok_or_else(|| ApiError::not_found("Verification not found"))
+    }
+
+    fn set_verification(
+        &self,
+        principal: Principal,
+        level: VerificationLevel,
+        provider_reference: String,
+        expires_at: Option<u64>,
+        caller: Principal,
+    ) -> Result<(), ApiError> {
+        if principal == Principal::anonymous() {
+            return Err(ApiError::invalid_argument("Anonymous principal cannot be verified"));
+        }
+        if provider_reference.is_empty() || provider_reference.len() > MAX_PROVIDER_REFERENCE_SIZE {
+            return Err(ApiError::invalid_argument(&format!(
+                "Provider reference must be between 1 and {} bytes",
+                MAX_PROVIDER_REFERENCE_SIZE
+            )));
+        }
+        let now = ic_cdk::api::time();
+        if expires_at.is_some_and(|expires_at| expires_at <= now) {
+            return Err(ApiError::invalid_argument("Expiry must be in the future"));
+        }
+
+        let verification = Verification {
+            level,
+            provider_reference,
+            expires_at,
+            verified_by: caller,
+            verified_at: now,
+        };
+        let before = self.verification_repository.get_verification(&principal);
+        let entry = AuditEntry::new(caller, AuditAction::VerificationUpdated)
+            .with_subject(principal)
+            .with_change(before.as_ref().map(describe), Some(describe(&verification)));
+        self.verification_repository.set_verification(principal, verification);
+        self.audit_repository.append_entry(entry);
+        Ok(())
+    }
+
+    fn remove_verification(&self, principal: Principal, caller: Principal) -> Result<(), ApiError> {
+        let before = self.verification_repository.remove_verification(&principal)
+            .ok_or_else(|| ApiError::not_found("Verification not found"))?;
+
+        let entry = AuditEntry::new(caller, AuditAction::VerificationRemoved)
+            .with_subject(principal)
+            .with_change(Some(describe(&before)), None);
+        self.audit_repository.append_entry(entry);
+        Ok(())
+    }
+
+    /// Fails if the configured requirements ask for a verification level at this amount that the principal lacks.
+    /// Without amount the highest configured level is required.
+    /// A party without principal, e.g. an unlinked Ethereum signatory, can't be verified.
+    fn assert_verified(&self, principal: Option<Principal>, amount: Option<u64>) -> Result<(), ApiError> {
+        let requirements = self.config_repository.get_config().verification_requirements.unwrap_or_default();
+        let Some(level) = required_verification_level(&requirements, amount) else {
+            return Ok(());
+        };
+
+        let verified = principal
+            .and_then(|principal| self.verification_repository.get_verification(&principal))
+            .is_some_and(|verification| verification.satisfies(level));
+        if !verified {
+            let party = principal.map_or("Party".to_string(), |principal| principal.to_text());
+            return Err(ApiError::permission_denied(&format!(
+                "{} must hold a valid {:?} verification to trade {}",
+                party,
+                level,
+                if amount.is_some() { "this amount" } else { "a contract without price" }
+            )));
+        }
+
+        Ok(())
+    }
+}
\ No newline at end of file
@@ -5,13 +5,15 @@ use alloy::{
     transports::icp::{EthSepoliaService,RpcService},
     eips::BlockNumberOrTag,
     signers::icp::IcpSigner,
-    primitives::{Address, U256, address},
+    primitives::{Address, B256, U256, address},
     providers::{Provider, ProviderBuilder},
     rpc::client::{ClientBuilder, IcpClient},
     sol,
     transports::icp::IcpConfig,
 };
 
+use ic_cdk::api::management_canister::ecdsa::{ecdsa_public_key, EcdsaCurve, EcdsaKeyId, EcdsaPublicKeyArgument};
+
 use crate::repositories::ApiError;
 
 
@@ -20,6 +22,12 @@ pub trait WalletService {
     async fn get_address(&self) -> Result<String, ApiError>;
     async fn get_balance_usdc(&self, address: Option<String>) -> Result<String, ApiError>;
     async fn transfer_usdc(&self, amount: u64, to: Address) -> Result<String, ApiError>;
+    async fn anchor_root(&self, registry: Address, root: B256, count: u64) -> Result<B256, ApiError>;
+    async fn get_transaction_status(&self, tx_hash: B256) -> Result<Option<bool>, ApiError>;
+    fn chain_id(&self) -> u64;
+    async fn get_derived_address(&self, derivation_path: Vec<Vec<u8>>) -> Result<String, ApiError>;
+    async fn get_public_key(&self, derivation_path: Vec<Vec<u8>>) -> Result<Vec<u8>, ApiError>;
+    async fn sign_hash(&self, hash: B256, derivation_path: Vec<Vec<u8>>) -> Result<Vec<u8>, ApiError>;
 }
 
 
@@ -31,6 +39,17 @@ sol!(
     "abi/USDC.json"
 );
 
+// Registry the Merkle roots of signed contracts are anchored in.
+sol!(
+    #[allow(missing_docs)]
+    #[sol(rpc)]
+    contract ContractRegistry {
+        event RootAnchored(bytes32 indexed root, uint256 count, address indexed submitter);
+
+        function anchor(bytes32 root, uint256 count) external;
+    }
+);
+
 static USDC_CONTRACT_ADDRESS: &'static str = "1c7d4b196cb0c7b01d743fbc6116a902379c7238";
 static LINK_CONTRACT_ADDRESS: &'static str = "779877A7B0D9E8603169DdbD7836e478b4624789";
 static CONTRACT_ADDRESS: &'static str = LINK_CONTRACT_ADDRESS;
@@ -68,9 +87,14 @@ fn get_ecdsa_key_name() -> String {
     }
 }
 
+/// Signer for the treasury key, the canister's threshold ECDSA key with an empty derivation path
 async fn create_icp_signer() -> IcpSigner {
+    create_derived_icp_signer(vec![]).await
+}
+
+async fn create_derived_icp_signer(derivation_path: Vec<Vec<u8>>) -> IcpSigner {
     let ecdsa_key_name = get_ecdsa_key_name();
-    IcpSigner::new(vec![], &ecdsa_key_name, None).await.unwrap()
+    IcpSigner::new(derivation_path, &ecdsa_key_name, None).await.unwrap()
 }
 
 
@@ -98,10 +122,7 @@ impl WalletService for WalletServiceImpl {
 
     /// Get the Ethereum address of the backend canister.
     async fn get_address(&self) -> Result<String, ApiError> {
-        let ecdsa_key_name = get_ecdsa_key_name();
-        let signer = IcpSigner::new(vec![], &ecdsa_key_name, None).await.unwrap();
-        let address = signer.address();
-        Ok(address.to_string())
+        self.get_derived_address(vec![]).await
     }
 
 
@@ -207,4 +228,105 @@ impl WalletService for WalletServiceImpl {
         }
     }
 
+    /// Write a Merkle root of contract hashes to the registry contract and return the transaction hash.
+    /// The transaction is only submitted, see get_transaction_status.
+    async fn anchor_root(&self, registry: Address, root: B256, count: u64) -> Result<B256, ApiError> {
+        let signer = create_icp_signer().await;
+        let address = signer.address();
+
+        let wallet = EthereumWallet::from(signer);
+        let rpc_service = if self.use_testnet {
+            get_rpc_service_sepolia()
+        } else {
+            get_rpc_service_base()
+        };
+
+        let config = IcpConfig::new(rpc_service)
+        .set_max_response_size(2000);
+
+        let provider = ProviderBuilder::new()
+            .with_recommended_fillers()
+            .wallet(wallet)
+            .on_icp(config);
+
+        let contract = ContractRegistry::new(registry, provider);
+
+        let chain_id = if self.use_testnet {
+            SEPOLIA_CHAIN_ID
+        } else {
+            BASE_CHAIN_ID
+        };
+        match contract
+            .anchor(root, U256::from(count))
+            .chain_id(chain_id)
+            .from(address)
+            .send()
+            .await
+        {
+            Ok(builder) => Ok(*builder.tx_hash()),
+            Err(e) => Err(ApiError::internal(e.to_string().as_str())),
+        }
+    }
+
+    /// Get the status of a transaction from its receipt: none while it is not mined,
+    /// otherwise whether it succeeded or reverted.
+    async fn get_transaction_status(&self, tx_hash: B256) -> Result<Option<bool>, ApiError> {
+        let rpc_service = if self.use_testnet {
+            get_rpc_service_sepolia()
+        } else {
+            get_rpc_service_base()
+        };
+
+        // receipts carry the logs of the transaction, a registry call emits a single event
+        let config = IcpConfig::new(rpc_service)
+        .set_max_response_size(5000);
+        let provider = ProviderBuilder::new().on_icp(config);
+
+        match provider.get_transaction_receipt(tx_hash).await {
+            Ok(receipt) => Ok(receipt.map(|receipt| receipt.status())),
+            Err(e) => Err(ApiError::internal(e.to_string().as_str())),
+        }
+    }
+
+    fn chain_id(&self) -> u64 {
+        if self.use_testnet {
+            SEPOLIA_CHAIN_ID
+        } else {
+            BASE_CHAIN_ID
+        }
+    }
+
+    /// Get the Ethereum address of the canister's threshold ECDSA key derived with the given path.
+    async fn get_derived_address(&self, derivation_path: Vec<Vec<u8>>) -> Result<String, ApiError> {
+        let signer = create_derived_icp_signer(derivation_path).await;
+        Ok(signer.address().to_string())
+    }
+
+    /// Get the SEC1 compressed public key of the canister's threshold ECDSA key derived with the given path.
+    async fn get_public_key(&self, derivation_path: Vec<Vec<u8>>) -> Result<Vec<u8>, ApiError> {
+        let args = EcdsaPublicKeyArgument {
+            canister_id: None,
+            derivation_path,
+            key_id: EcdsaKeyId { curve: EcdsaCurve::Secp256k1, name: get_ecdsa_key_name() },
+        };
+
+        let (reply,) = ecdsa_public_key(args)
+            .await
+            .map_err(|(code, message)| {
+                ApiError::internal(&format!("ecdsa_public_key failed: {:?} {}", code, message))
+            })?;
+
+        Ok(reply.public_key)
+    }
+
+    /// Sign a 32 bytes hash with the canister's threshold ECDSA key derived with the given path, returns r ++ s ++ v.
+    async fn sign_hash(&self, hash: B256, derivation_path: Vec<Vec<u8>>) -> Result<Vec<u8>, ApiError> {
+        let signer = create_derived_icp_signer(derivation_path).await;
+        let signature = signer.sign_hash(&hash)
+            .await
+            .map_err(|e| ApiError::internal(e.to_string().as_str()))?;
+
+        Ok(signature.as_bytes().to_vec())
+    }
+
 }
@@ -0,0 +1,195 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use candid::Nat;
+use ic_certification::{fork, labeled, leaf, pruned, AsHashTree, HashTree, RbTree};
+use serde::Serialize;
+use crate::repositories::{ApiError, Hash};
+
+/// Data certified by the canister, the root hash of this tree is set as certified data:
+///
+/// ```text
+/// contracts/<contract id>: sha256 of the candid encoded contract
+/// last_block_hash: hash of the last block of the ICRC-3 log
+/// last_block_index: leb128 encoded index of the last block of the ICRC-3 log
+/// sig/<seed hash>/<delegation hash>: empty leaf for every SIWE delegation signed by the canister
+/// signed/<contract id>: 1 if the contract is signed, 0 otherwise
+/// ```
+///
+/// The tree lives on the heap, so it is rebuilt from stable memory after an upgrade.
+#[derive(Default)]
+struct CertifiedState {
+    contracts: RbTree<Vec<u8>, Hash>,
+    signed: RbTree<Vec<u8>, Vec<u8>>,
+    icrc3_tip: Option<(u64, Hash)>,
+    sigs: RbTree<Hash, RbTree<Hash, Vec<u8>>>,
+    /// expiration, seed hash and delegation hash of the signatures in sigs, in insertion order
+    sig_expirations: VecDeque<(u64, Hash, Hash)>,
+}
+
+/// Which parts of the tree are revealed in a witness, everything else is pruned
+enum Reveal<'a> {
+    Nothing,
+    Contract(&'a [u8]),
+    Signed(&'a [u8]),
+    ICRC3Tip,
+    Signature(&'a Hash, &'a Hash),
+}
+
+impl CertifiedState {
+    fn tree(&self, reveal: Reveal) -> HashTree {
+        let contracts = match reveal {
+            Reveal::Contract(key) => self.contracts.witness(key),
+            _ => pruned(self.contracts.root_hash()),
+        };
+        let signed = match reveal {
+            Reveal::Signed(key) => self.signed.witness(key),
+            _ => pruned(self.signed.root_hash()),
+        };
+        let sigs = match reveal {
+            Reveal::Signature(seed_hash, delegation_hash) => {
+                self.sigs.nested_witness(seed_hash, |delegations| delegations.witness(delegation_hash))
+            }
+            _ => pruned(self.sigs.root_hash()),
+        };
+        let contracts = labeled("contracts", contracts);
+        let signed = fork(labeled("sig", sigs), labeled("signed", signed));
+
+        // labels are kept in sorted order
+        match self.icrc3_tip {
+            Some((index, hash)) => {
+                let (last_block_hash, last_block_index) = match reveal {
+                    Reveal::ICRC3Tip => (leaf(hash.to_vec()), leaf(leb128(index))),
+                    _ => (pruned(leaf(hash.to_vec()).digest()), pruned(leaf(leb128(index)).digest())),
+                };
+                fork(
+                    fork(contracts, labeled("last_block_hash", last_block_hash)),
+                    fork(labeled("last_block_index", last_block_index), signed),
+                )
+            }
+            None => fork(contracts, signed),
+        }
+    }
+
+    fn certify(&self) {
+        ic_cdk::api::set_certified_data(&self.tree(Reveal::Nothing).digest());
+    }
+
+    fn prune_expired_signatures(&mut self, now: u64) {
+        while let Some((expiration, seed_hash, delegation_hash)) = self.sig_expirations.front().copied() {
+            if expiration > now {
+                break;
+            }
+            self.sig_expirations.pop_front();
+
+            let mut is_empty = false;
+            self.sigs.modify(&seed_hash, |delegations| {
+                delegations.delete(&delegation_hash);
+                is_empty = delegations.is_empty();
+            });
+            if is_empty {
+                self.sigs.delete(&seed_hash);
+            }
+        }
+    }
+}
+
+thread_local! {
+    static STATE: RefCell<CertifiedState> = RefCell::new(CertifiedState::default());
+}
+
+fn leb128(index: u64) -> Vec<u8> {
+    let mut bytes = vec![];
+    Nat::from(index).encode(&mut bytes).expect("failed to leb128 encode block index");
+    bytes
+}
+
+/// Update the certified hash and signature status of a contract.
+/// Call certify_data afterwards, or use certify_contract for a single contract.
+pub fn set_certified_contract(contract_id: &str, contract_hash: Hash, is_signed: bool) {
+    STATE.with_borrow_mut(|state| {
+        let key = contract_id.as_bytes().to_vec();
+        state.contracts.insert(key.clone(), contract_hash);
+        state.signed.insert(key, vec![is_signed as u8]);
+    });
+}
+
+pub fn certify_contract(contract_id: &str, contract_hash: Hash, is_signed: bool) {
+    set_certified_contract(contract_id, contract_hash, is_signed);
+    certify_data();
+}
+
+/// Update the certified index and hash of the last ICRC-3 block
+pub fn certify_icrc3_tip(last_block_index: u64, last_block_hash: Hash) {
+    STATE.with_borrow_mut(|state| state.icrc3_tip = Some((last_block_index, last_block_hash)));
+    certify_data();
+}
+
+/// Set the root hash of the certified tree as the certified data of the canister
+pub fn certify_data() {
+    STATE.with_borrow(|state| state.certify());
+}
+
+/// Witness for the hash of a contract, at path contracts/<contract id>
+pub fn contract_witness(contract_id: &str) -> HashTree {
+    STATE.with_borrow(|state| state.tree(Reveal::Contract(contract_id.as_bytes())))
+}
+
+/// Witness for the signature status of a contract, at path signed/<contract id>
+pub fn signed_witness(contract_id: &str) -> HashTree {
+    STATE.with_borrow(|state| state.tree(Reveal::Signed(contract_id.as_bytes())))
+}
+
+/// Witness for last_block_index and last_block_hash, as required by ICRC-3
+pub fn icrc3_tip_witness() -> HashTree {
+    STATE.with_borrow(|state| state.tree(Reveal::ICRC3Tip))
+}
+
+/// Add the canister signature of a delegation at sig/<seed hash>/<delegation hash> and certify it.
+/// Expired signatures are removed on the way.
+pub fn certify_delegation(seed_hash: Hash, delegation_hash: Hash, expiration: u64) {
+    STATE.with_borrow_mut(|state| {
+        state.prune_expired_signatures(ic_cdk::api::time());
+
+        if state.sigs.get(&seed_hash).is_none() {
+            state.sigs.insert(seed_hash, RbTree::new());
+        }
+        state.sigs.modify(&seed_hash, |delegations| delegations.insert(delegation_hash, vec![]));
+        state.sig_expirations.push_back((expiration, seed_hash, delegation_hash));
+    });
+    certify_data();
+}
+
+/// Witness for the canister signature of a delegation, at path sig/<seed hash>/<delegation hash>
+pub fn delegation_witness(seed_hash: &Hash, delegation_hash: &Hash) -> Option<HashTree> {
+    STATE.with_borrow(|state| {
+        state.sigs.get(seed_hash)?.get(delegation_hash)?;
+        Some(state.tree(Reveal::Signature(seed_hash, delegation_hash)))
+    })
+}
+
+/// Serialize a hash tree to self-describing CBOR, as expected in certificates
+pub fn encode_hash_tree(tree: &HashTree) -> Result<Vec<u8>, ApiError> {
+    let mut serializer = serde_cbor::Serializer::new(vec![]);
+    serializer.self_describe().map_err(|e| ApiError::internal(&e.to_string()))?;
+    tree.serialize(&mut serializer).map_err(|e| ApiError::internal(&e.to_string()))?;
+
+    Ok(serializer.into_inner())
+}
+
+#[derive(Serialize)]
+struct CanisterSignature<'a> {
+    #[serde(with = "serde_bytes")]
+    certificate: &'a [u8],
+    tree: &'a HashTree,
+}
+
+/// Serialize a canister signature, the certificate with the witness of the signed message, to self-describing CBOR
+pub fn encode_canister_signature(certificate: &[u8], tree: &HashTree) -> Result<Vec<u8>, ApiError> {
+    let mut serializer = serde_cbor::Serializer::new(vec![]);
+    serializer.self_describe().map_err(|e| ApiError::internal(&e.to_string()))?;
+    CanisterSignature { certificate, tree }
+        .serialize(&mut serializer)
+        .map_err(|e| ApiError::internal(&e.to_string()))?;
+
+    Ok(serializer.into_inner())
+}
\ No newline at end of file
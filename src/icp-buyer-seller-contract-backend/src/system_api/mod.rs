@@ -1,5 +1,9 @@
+mod certified_data;
 mod rand;
 mod time;
+mod vetkd;
 
+pub use certified_data::*;
 pub use rand::*;
 pub use time::*;
+pub use vetkd::*;
@@ -0,0 +1,102 @@
+use candid::{CandidType, Deserialize, Principal};
+use crate::repositories::ApiError;
+
+// Cycles attached to vetkd_derive_key, enough for the production key. Unused cycles are refunded.
+const VETKD_DERIVE_KEY_CYCLES: u128 = 26_153_846_153;
+
+#[derive(CandidType, Deserialize)]
+enum VetKDCurve {
+    #[serde(rename = "bls12_381_g2")]
+    Bls12_381G2,
+}
+
+#[derive(CandidType, Deserialize)]
+struct VetKDKeyId {
+    curve: VetKDCurve,
+    name: String,
+}
+
+#[derive(CandidType, Deserialize)]
+struct VetKDPublicKeyArgs {
+    canister_id: Option<Principal>,
+    context: Vec<u8>,
+    key_id: VetKDKeyId,
+}
+
+#[derive(CandidType, Deserialize)]
+struct VetKDPublicKeyReply {
+    public_key: Vec<u8>,
+}
+
+#[derive(CandidType, Deserialize)]
+struct VetKDDeriveKeyArgs {
+    input: Vec<u8>,
+    context: Vec<u8>,
+    transport_public_key: Vec<u8>,
+    key_id: VetKDKeyId,
+}
+
+#[derive(CandidType, Deserialize)]
+struct VetKDDeriveKeyReply {
+    encrypted_key: Vec<u8>,
+}
+
+fn get_vetkd_key_id() -> VetKDKeyId {
+    #[allow(clippy::option_env_unwrap)]
+    let dfx_network = option_env!("DFX_NETWORK").unwrap();
+    let name = match dfx_network {
+        "local" => "dfx_test_key",
+        "ic" => "key_1",
+        _ => panic!("Unsupported network."),
+    };
+
+    VetKDKeyId {
+        curve: VetKDCurve::Bls12_381G2,
+        name: name.to_string(),
+    }
+}
+
+/// Get the vetKD public key of this canister for the given context
+pub async fn vetkd_public_key(context: Vec<u8>) -> Result<Vec<u8>, ApiError> {
+    let args = VetKDPublicKeyArgs {
+        canister_id: None,
+        context,
+        key_id: get_vetkd_key_id(),
+    };
+
+    let (reply,): (VetKDPublicKeyReply,) =
+        ic_cdk::call(Principal::management_canister(), "vetkd_public_key", (args,))
+            .await
+            .map_err(|(code, message)| {
+                ApiError::internal(&format!("vetkd_public_key failed: {:?} {}", code, message))
+            })?;
+
+    Ok(reply.public_key)
+}
+
+/// Derive the vetKey for the given context and input, encrypted under the transport public key
+pub async fn vetkd_derive_key(
+    context: Vec<u8>,
+    input: Vec<u8>,
+    transport_public_key: Vec<u8>,
+) -> Result<Vec<u8>, ApiError> {
+    let args = VetKDDeriveKeyArgs {
+        input,
+        context,
+        transport_public_key,
+        key_id: get_vetkd_key_id(),
+    };
+
+    let (reply,): (VetKDDeriveKeyReply,) = ic_cdk::api::call::call_with_payment128(
+        Principal::management_canister(),
+        "vetkd_derive_key",
+        (args,),
+        VETKD_DERIVE_KEY_CYCLES,
+    )
+    .await
+    .map_err(|(code, message)| {
+        ApiError::internal(&format!("vetkd_derive_key failed: {:?} {}", code, message))
+    })?;
+
+    Ok(reply.encrypted_key)
+}